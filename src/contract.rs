@@ -1,16 +1,24 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdError,
-    StdResult, SubMsgResult, Uint128,
+    to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    SubMsgResult, Uint128,
 };
 use cw2::set_contract_version;
-use entropy_beacon_cosmos::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use entropy_beacon_cosmos::msg::InstantiateMsg;
 
-use crate::{error::ContractError, msg::MigrateMsg, query};
+use crate::{
+    error::ContractError,
+    msg::{ExecuteMsg, MigrateMsg, QueryMsg, SudoMsg},
+    query, sudo,
+    utils::nonpayable,
+};
 use crate::{
     execute,
-    state::{Config, State, CONFIG, STATE, WHITELISTED_KEYS},
+    migrations::run_migrations,
+    state::{
+        Config, ForeignDenomPolicy, PauseFlags, State, CONFIG, IMPORT_OPEN, STATE, WHITELISTED_KEYS,
+    },
 };
 use crate::{msg::SUBMSG_REPLY_ID, state::KeyInfo};
 
@@ -31,24 +39,36 @@ pub fn instantiate(
         last_entropy: None,
         belief_gas_price: msg.belief_gas_price,
         cur_request_id: 0u128,
+        cur_config_change_id: 0,
+        pending_requests: 0,
+        cur_gas_price_record_id: 0,
     };
 
     let cfg = Config {
-        owner: info.sender.clone(),
+        owner: Some(info.sender.clone()),
         whitelist_deposit_amt: msg.whitelist_deposit_amt,
         refund_increment_amt: msg.refund_increment_amt,
         key_activation_delay: msg.key_activation_delay,
         protocol_fee: msg.protocol_fee,
         submitter_share: Decimal::percent(msg.submitter_share),
         native_denom: msg.native_denom,
-        paused: false,
+        pause_flags: PauseFlags::default(),
         permissioned: msg.permissioned,
         test_mode: msg.test_mode,
         subsidize_callbacks: msg.subsidize_callbacks,
+        config_timelock: 0,
+        sunset: false,
+        successor: None,
+        predecessor: None,
+        fee_split: None,
+        foreign_denoms: ForeignDenomPolicy::Reject,
+        gas_price_adjustment: None,
     };
 
+    cfg.validate()?;
     STATE.save(deps.storage, &state)?;
     CONFIG.save(deps.storage, &cfg)?;
+    IMPORT_OPEN.save(deps.storage, &true)?;
 
     for (addr, key) in msg.whitelisted_keys {
         if key.validate().is_err() {
@@ -62,6 +82,8 @@ pub fn instantiate(
                 deposit_amount: Uint128::zero(),
                 refundable_amount: Uint128::zero(),
                 creation_height: env.block.height,
+                payout_address: None,
+                deposit_denom: cfg.native_denom.clone(),
             },
         )?;
     }
@@ -78,7 +100,22 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    match msg {
+    // Funds sent along with messages that are not paid for are never kept.
+    let refund = match msg {
+        ExecuteMsg::WhitelistPublicKey(_)
+        | ExecuteMsg::RequestEntropy(_)
+        | ExecuteMsg::ReceiveForwardedRequests(_)
+        | ExecuteMsg::Receive(_)
+        | ExecuteMsg::DepositCredit(_)
+        | ExecuteMsg::FundSponsorship(_)
+        | ExecuteMsg::GrantFee(_) => None,
+        _ => {
+            let cfg = CONFIG.load(deps.storage)?;
+            execute::refund_foreign(&info.sender, nonpayable(&cfg, &info.funds)?)
+        }
+    };
+
+    let response = match msg {
         ExecuteMsg::UpdateConfig(data) => execute::update_config(deps, env, info, data),
         ExecuteMsg::WhitelistPublicKey(data) => execute::whitelist_key(deps, env, info, data),
         ExecuteMsg::ReclaimDeposit(data) => execute::reclaim_deposit(deps, env, info, data),
@@ -90,6 +127,61 @@ pub fn execute(
         ExecuteMsg::UpdateGasPrice(gas_price) => {
             execute::update_gas_price(deps, env, info, gas_price)
         }
+        ExecuteMsg::ProposeOwner(data) => execute::propose_owner(deps, env, info, data),
+        ExecuteMsg::AcceptOwnership(data) => execute::accept_ownership(deps, env, info, data),
+        ExecuteMsg::CancelOwnershipTransfer(data) => {
+            execute::cancel_ownership_transfer(deps, env, info, data)
+        }
+        ExecuteMsg::RenounceOwnership(data) => execute::renounce_ownership(deps, env, info, data),
+        ExecuteMsg::GrantRole(data) => execute::grant_role(deps, env, info, data),
+        ExecuteMsg::RevokeRole(data) => execute::revoke_role(deps, env, info, data),
+        ExecuteMsg::SetPaused(data) => execute::set_paused(deps, env, info, data),
+        ExecuteMsg::UpdateConfigTimelock(data) => {
+            execute::update_config_timelock(deps, env, info, data)
+        }
+        ExecuteMsg::ApplyConfigChange(data) => execute::apply_config_change(deps, env, info, data),
+        ExecuteMsg::CancelConfigChange(data) => {
+            execute::cancel_config_change(deps, env, info, data)
+        }
+        ExecuteMsg::ExitTestMode(data) => execute::exit_test_mode(deps, env, info, data),
+        ExecuteMsg::StartSunset(data) => execute::start_sunset(deps, env, info, data),
+        ExecuteMsg::ProcessSunset(data) => execute::process_sunset(deps, env, info, data),
+        ExecuteMsg::SetSuccessor(data) => execute::set_successor(deps, env, info, data),
+        ExecuteMsg::SetPredecessor(data) => execute::set_predecessor(deps, env, info, data),
+        ExecuteMsg::ForwardRequests(data) => execute::forward_requests(deps, env, info, data),
+        ExecuteMsg::ReceiveForwardedRequests(data) => {
+            execute::receive_forwarded_requests(deps, env, info, data)
+        }
+        ExecuteMsg::ImportState(data) => execute::import_state(deps, env, info, data),
+        ExecuteMsg::WithdrawRevenue(data) => execute::withdraw_revenue(deps, env, info, data),
+        ExecuteMsg::SetFeeSplit(data) => execute::set_fee_split(deps, env, info, data),
+        ExecuteMsg::ClaimRewards(data) => execute::claim_rewards(deps, env, info, data),
+        ExecuteMsg::SetPayoutAddress(data) => execute::set_payout_address(deps, env, info, data),
+        ExecuteMsg::SetForeignDenomPolicy(data) => {
+            execute::set_foreign_denom_policy(deps, env, info, data)
+        }
+        ExecuteMsg::SetFeeDenom(data) => execute::set_fee_denom(deps, env, info, data),
+        ExecuteMsg::Receive(data) => execute::receive_cw20(deps, env, info, data),
+        ExecuteMsg::DepositCredit(data) => execute::deposit_credit(deps, env, info, data),
+        ExecuteMsg::WithdrawCredit(data) => execute::withdraw_credit(deps, env, info, data),
+        ExecuteMsg::FundSponsorship(data) => execute::fund_sponsorship(deps, env, info, data),
+        ExecuteMsg::DrainSponsorship(data) => execute::drain_sponsorship(deps, env, info, data),
+        ExecuteMsg::GrantFee(data) => execute::grant_fee(deps, env, info, data),
+        ExecuteMsg::RevokeFeeGrant(data) => execute::revoke_fee_grant(deps, env, info, data),
+        ExecuteMsg::SetGasPriceAdjustment(data) => {
+            execute::set_gas_price_adjustment(deps, env, info, data)
+        }
+    }?;
+    Ok(response.add_messages(refund))
+}
+
+/// Handles privileged messages sent by chain governance.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::SetPaused(data) => sudo::set_paused(deps, env, data),
+        SudoMsg::UpdateOwner(data) => sudo::update_owner(deps, env, data),
+        SudoMsg::ForceReturnDeposit(data) => sudo::force_return_deposit(deps, env, data),
     }
 }
 
@@ -117,21 +209,52 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::ActiveRequests(data) => to_binary(&query::active_requests_query(deps, data)?),
         QueryMsg::BeaconConfig(_) => to_binary(&query::beacon_config_query(deps)?),
         QueryMsg::CalculateFee(data) => to_binary(&query::calculate_fee_query(deps, data)?),
+        QueryMsg::Ownership(_) => to_binary(&query::ownership_query(deps)?),
+        QueryMsg::Roles(data) => to_binary(&query::roles_query(deps, data)?),
+        QueryMsg::RoleMembers(data) => to_binary(&query::role_members_query(deps, data)?),
+        QueryMsg::PendingConfigChanges(data) => {
+            to_binary(&query::pending_config_changes_query(deps, data)?)
+        }
+        QueryMsg::PauseFlags(_) => to_binary(&query::pause_flags_query(deps)?),
+        QueryMsg::AuditLog(data) => to_binary(&query::audit_log_query(deps, data)?),
+        QueryMsg::ValidateConfigUpdate(data) => {
+            to_binary(&query::validate_config_update_query(deps, data)?)
+        }
+        QueryMsg::SunsetStatus(_) => to_binary(&query::sunset_status_query(deps)?),
+        QueryMsg::ExportState(data) => to_binary(&query::export_state_query(deps, data)?),
+        QueryMsg::ProtocolRevenue(_) => to_binary(&query::protocol_revenue_query(deps)?),
+        QueryMsg::Solvency(data) => to_binary(&query::solvency_query(deps, env, data)?),
+        QueryMsg::FeeSplit(_) => to_binary(&query::fee_split_query(deps)?),
+        QueryMsg::UnclaimedRewards(data) => to_binary(&query::unclaimed_rewards_query(deps, data)?),
+        QueryMsg::FeeDenoms(_) => to_binary(&query::fee_denoms_query(deps)?),
+        QueryMsg::Credit(data) => to_binary(&query::credit_query(deps, data)?),
+        QueryMsg::SponsorshipPool(data) => to_binary(&query::sponsorship_pool_query(deps, data)?),
+        QueryMsg::SponsorshipSpends(data) => {
+            to_binary(&query::sponsorship_spends_query(deps, data)?)
+        }
+        QueryMsg::FeeGrant(data) => to_binary(&query::fee_grant_query(deps, env, data)?),
+        QueryMsg::FeeGrants(data) => to_binary(&query::fee_grants_query(deps, env, data)?),
+        QueryMsg::GasPriceHistory(data) => to_binary(&query::gas_price_history_query(deps, data)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
     let version = cw2::get_contract_version(deps.storage)?;
 
-    if version.version != "2.1.0" {
-        return Err(ContractError::Std(StdError::generic_err(format!(
-            "Invalid version for migration: {}",
-            version.version
-        ))));
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidMigrationContract {
+            name: version.contract,
+        });
     }
 
+    let applied = run_migrations(deps.branch(), &env, &version.version, CONTRACT_VERSION)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    Ok(Response::new().add_attribute("action", "migrate"))
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", version.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("steps", applied.join(",")))
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -41,4 +41,108 @@ pub enum ContractError {
 
     #[error("No matching active request {request_id}")]
     NoMatchingRequests { request_id: u128 },
+
+    #[error("No pending ownership transfer")]
+    NoPendingOwner {},
+
+    #[error("Role {role} has not been granted to {address}")]
+    RoleNotGranted { role: String, address: String },
+
+    #[error("No matching scheduled config change {change_id}")]
+    NoMatchingConfigChange { change_id: u64 },
+
+    #[error("Config change not ready yet (applicable at height {effective_height})")]
+    ConfigChangeNotReady { effective_height: u64 },
+
+    #[error("Submitter share must not exceed 100%")]
+    InvalidSubmitterShare {},
+
+    #[error("Native denom must not be empty")]
+    EmptyNativeDenom {},
+
+    #[error("Native denom {denom} is already accepted as a fee denom")]
+    NativeDenomIsFeeDenom { denom: String },
+
+    #[error("Deposits or bounties are still held in the native denom {denom}")]
+    NativeDenomInUse { denom: String },
+
+    #[error("Refund increment must not exceed the whitelist deposit")]
+    RefundIncrementExceedsDeposit {},
+
+    #[error("Beacon is not in test mode")]
+    NotInTestMode {},
+
+    #[error("Requests made under test mode are still pending")]
+    PendingTestModeRequests {},
+
+    #[error("Cannot migrate from version {version}")]
+    UnsupportedMigrationVersion { version: String },
+
+    #[error("Cannot migrate from {from} down to {to}")]
+    MigrationDowngrade { from: String, to: String },
+
+    #[error("Cannot migrate from contract {name}")]
+    InvalidMigrationContract { name: String },
+
+    #[error("Beacon is being wound down")]
+    Sunset {},
+
+    #[error("Beacon is not being wound down")]
+    NotInSunset {},
+
+    #[error("No successor beacon has been designated")]
+    NoSuccessor {},
+
+    #[error("State can no longer be imported into this beacon")]
+    ImportClosed {},
+
+    #[error("Unsupported state export version {version}")]
+    UnsupportedExportVersion { version: u32 },
+
+    #[error("Insufficient protocol revenue (available: {available})")]
+    InsufficientRevenue { available: Uint128 },
+
+    #[error("Payment would leave the beacon short of {shortfall}{denom}")]
+    Insolvent { denom: String, shortfall: Uint128 },
+
+    #[error("Insufficient rewards (available: {available})")]
+    InsufficientRewards { available: Uint128 },
+
+    #[error("Insufficient credit (available: {available})")]
+    InsufficientCredit { available: Uint128 },
+
+    #[error("Funds must not be sent when paying from credit")]
+    FundsWithCredit {},
+
+    #[error("Fee grant not found")]
+    FeeGrantNotFound {},
+
+    #[error("Fee grants must expire after the current block")]
+    InvalidFeeGrantExpiry {},
+
+    #[error("Requests cannot be referred by their requester or callback address")]
+    InvalidReferrer {},
+
+    #[error("The current fee of {fee} exceeds the maximum of {max_fee}")]
+    FeeExceedsMax { fee: Uint128, max_fee: Uint128 },
+
+    #[error("Bounty must cover the current fee of {fee}")]
+    BountyBelowFee { fee: Uint128 },
+
+    #[error("Fee split shares must sum to 100%")]
+    InvalidFeeSplit {},
+
+    #[error(
+        "Gas price adjustment needs a non-zero target, min <= max and a max change of at most 100%"
+    )]
+    InvalidGasPriceAdjustment {},
+
+    #[error("Fee denoms must not be empty or the native denom")]
+    InvalidFeeDenom {},
+
+    #[error("Unsupported denom: {denom}")]
+    UnsupportedDenom { denom: String },
+
+    #[error("Funds must not be sent with this message")]
+    NonPayable {},
 }
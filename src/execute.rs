@@ -1,40 +1,309 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
 
 use cosmwasm_std::{
-    BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Order, ReplyOn, Response,
-    StdError, SubMsg, Uint128,
+    from_binary, to_binary, Addr, BankMsg, Coin, Decimal, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, ReplyOn, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
 };
-use ecvrf_rs::encode_hex;
+use cw20::Cw20ReceiveMsg;
+use ecvrf_rs::{encode_hex, PublicKey};
 use entropy_beacon_cosmos::{
-    beacon::{CalculateFeeQuery, RequestEntropyMsg, UpdateConfigMsg},
-    provide::{AdminReturnDepositMsg, ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg},
+    beacon::UpdateConfigMsg,
+    provide::{
+        AdminReturnDepositMsg, ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg,
+        DEFAULT_PAGINATION_LIMIT, MAX_PAGINATION_LIMIT,
+    },
     EntropyCallbackMsg,
 };
 use sha2::{Digest, Sha512};
 
-use crate::utils::{check_key, is_whitelisted};
+use crate::utils::{
+    add_coin, audit_change, balance, burn_coins, check_key, check_native_denom_change, check_owner,
+    check_role, config_changes, cw20_denom, cw20_token, deposit_terms, empty_config_update,
+    is_whitelisted, received_funds, record_audit, send_coins, split_funds, update_ledger,
+    update_ledger_for_payout,
+};
 use crate::{error::ContractError, state::ENTROPY_REQUESTS};
 use crate::{msg::SUBMSG_REPLY_ID, state::KeyInfo};
 use crate::{
+    msg::{
+        AcceptOwnershipMsg, ApplyConfigChangeMsg, CalculateFeeQuery, CancelConfigChangeMsg,
+        CancelOwnershipTransferMsg, ClaimRewardsMsg, DepositCreditMsg, DrainSponsorshipMsg,
+        ExecuteMsg, ExitTestModeMsg, ForwardRequestsMsg, ForwardedRequest, FundSponsorshipMsg,
+        GrantFeeMsg, GrantRoleMsg, ImportStateMsg, ProcessSunsetMsg, ProposeOwnerMsg,
+        ReceiveForwardedRequestsMsg, ReceiveMsg, RenounceOwnershipMsg, RequestEntropyMsg,
+        RevokeFeeGrantMsg, RevokeRoleMsg, SetFeeDenomMsg, SetFeeSplitMsg, SetForeignDenomPolicyMsg,
+        SetGasPriceAdjustmentMsg, SetPausedMsg, SetPayoutAddressMsg, SetPredecessorMsg,
+        SetSuccessorMsg, StartSunsetMsg, UpdateConfigTimelockMsg, WithdrawCreditMsg,
+        WithdrawRevenueMsg, STATE_EXPORT_VERSION,
+    },
     query,
-    state::{EntropyRequest, CONFIG, STATE, WHITELISTED_KEYS},
+    state::{
+        Config, EntropyRequest, FeeGrant, FeeGrantKey, FeeRecipient, GasPriceRecord, Ledger,
+        PauseFlags, PendingConfigChange, Role, SponsorshipSpend, CONFIG, CREDITS, FEE_DENOMS,
+        FEE_GRANTS, GAS_PRICE_HISTORY, IMPORT_OPEN, LEDGERS, PENDING_CONFIG_CHANGES, PENDING_OWNER,
+        REWARDS, ROLES, SPONSORSHIP_SPENDS, STATE, WHITELISTED_KEYS,
+    },
 };
-pub fn update_gas_price(
+
+/// Proposes a new owner for the contract.
+/// This is only allowed to be called by the owner, and only takes effect
+/// once the proposed owner calls `accept_ownership`.
+pub fn propose_owner(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    gas_price: Decimal,
+    data: ProposeOwnerMsg,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.paused {
-        return Err(ContractError::Paused {});
-    }
-    if cfg.owner != info.sender {
+    check_owner(&cfg, &info.sender)?;
+
+    let new_owner = deps.api.addr_validate(&data.new_owner)?;
+    let old_pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    PENDING_OWNER.save(deps.storage, &new_owner)?;
+
+    let changes = vec![audit_change(
+        "pending_owner",
+        &old_pending_owner,
+        &Some(new_owner.clone()),
+    )?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "propose_owner",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_owner")
+        .add_attribute("pending_owner", new_owner))
+}
+
+/// Completes an ownership transfer. Must be called by the pending owner.
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _data: AcceptOwnershipMsg,
+) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if pending_owner != info.sender {
         return Err(ContractError::Unauthorized {});
     }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let old_cfg = cfg.clone();
+    cfg.owner = Some(pending_owner.clone());
+    CONFIG.save(deps.storage, &cfg)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let mut changes = config_changes(&old_cfg, &cfg)?;
+    changes.push(audit_change("pending_owner", &Some(&pending_owner), &None)?);
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "accept_ownership",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", pending_owner))
+}
+
+/// Cancels a pending ownership transfer.
+/// This is only allowed to be called by the owner.
+pub fn cancel_ownership_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _data: CancelOwnershipTransferMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let changes = vec![audit_change("pending_owner", &Some(pending_owner), &None)?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "cancel_ownership_transfer",
+        changes,
+    )?;
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_transfer"))
+}
+
+/// Permanently removes the owner of the contract, along with any pending
+/// ownership transfer. This is only allowed to be called by the owner.
+pub fn renounce_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _data: RenounceOwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let old_cfg = cfg.clone();
+    cfg.owner = None;
+    CONFIG.save(deps.storage, &cfg)?;
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let mut changes = config_changes(&old_cfg, &cfg)?;
+    if pending_owner.is_some() {
+        changes.push(audit_change("pending_owner", &pending_owner, &None)?);
+    }
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "renounce_ownership",
+        changes,
+    )?;
+
+    Ok(Response::new().add_attribute("action", "renounce_ownership"))
+}
+
+/// Grants a role to an address.
+/// This is only allowed to be called by the owner.
+pub fn grant_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: GrantRoleMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let addr = deps.api.addr_validate(&data.address)?;
+    let granted = ROLES.has(deps.storage, (data.role.as_str(), &addr));
+    ROLES.save(deps.storage, (data.role.as_str(), &addr), &Empty {})?;
+
+    let field = format!("roles.{}.{}", data.role.as_str(), addr);
+    let changes = vec![audit_change(field, &granted, &true)?];
+    record_audit(deps.storage, &env, Some(info.sender), "grant_role", changes)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("role", data.role.as_str())
+        .add_attribute("address", addr))
+}
+
+/// Revokes a role from an address.
+/// This is only allowed to be called by the owner.
+pub fn revoke_role(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: RevokeRoleMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let addr = deps.api.addr_validate(&data.address)?;
+    if !ROLES.has(deps.storage, (data.role.as_str(), &addr)) {
+        return Err(ContractError::RoleNotGranted {
+            role: data.role.as_str().to_string(),
+            address: addr.to_string(),
+        });
+    }
+    ROLES.remove(deps.storage, (data.role.as_str(), &addr));
+
+    let field = format!("roles.{}.{}", data.role.as_str(), addr);
+    let changes = vec![audit_change(field, &true, &false)?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "revoke_role",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("role", data.role.as_str())
+        .add_attribute("address", addr))
+}
+
+/// Pauses or unpauses parts of the contract.
+/// This is only allowed to be called by a holder of the `Pauser` role.
+pub fn set_paused(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetPausedMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_role(&deps.as_ref(), &cfg, Role::Pauser, &info.sender)?;
+
+    update_pause_flags(deps, &env, Some(info.sender), cfg, data, "set_paused")
+}
+
+/// Applies every pause flag that is set in a `SetPausedMsg`.
+pub(crate) fn update_pause_flags(
+    deps: DepsMut,
+    env: &Env,
+    actor: Option<Addr>,
+    mut cfg: Config,
+    data: SetPausedMsg,
+    action: &str,
+) -> Result<Response, ContractError> {
+    let old_cfg = cfg.clone();
+    let flags = &mut cfg.pause_flags;
+    flags.requests = data.requests.unwrap_or(flags.requests);
+    flags.submissions = data.submissions.unwrap_or(flags.submissions);
+    flags.whitelisting = data.whitelisting.unwrap_or(flags.whitelisting);
+    flags.withdrawals = data.withdrawals.unwrap_or(flags.withdrawals);
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(deps.storage, env, actor, action, changes)?;
+
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("requests", cfg.pause_flags.requests.to_string())
+        .add_attribute("submissions", cfg.pause_flags.submissions.to_string())
+        .add_attribute("whitelisting", cfg.pause_flags.whitelisting.to_string())
+        .add_attribute("withdrawals", cfg.pause_flags.withdrawals.to_string()))
+}
+
+pub fn update_gas_price(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    gas_price: Decimal,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_role(&deps.as_ref(), &cfg, Role::GasPriceUpdater, &info.sender)?;
     let mut state = STATE.load(deps.storage)?;
+    let old_gas_price = state.belief_gas_price;
     state.belief_gas_price = gas_price;
     STATE.save(deps.storage, &state)?;
+
+    let changes = vec![audit_change(
+        "belief_gas_price",
+        &old_gas_price,
+        &gas_price,
+    )?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "update_gas_price",
+        changes,
+    )?;
+
     Ok(Response::new()
         .add_attribute("action", "update_gas_price")
         .add_attribute("gas_price", gas_price.to_string()))
@@ -42,68 +311,1140 @@ pub fn update_gas_price(
 
 pub fn admin_return_deposit(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     data: AdminReturnDepositMsg,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
     }
-    if !is_whitelisted(&deps.as_ref(), &data.key) {
+    check_role(&deps.as_ref(), &cfg, Role::KeyManager, &info.sender)?;
+
+    return_full_deposit(deps, &env, Some(info.sender), data.key, "reclaim_deposit")
+}
+
+/// Unwhitelists a key and returns its entire deposit to the key holder,
+/// regardless of how much of it has been unlocked through submissions.
+pub(crate) fn return_full_deposit(
+    mut deps: DepsMut,
+    env: &Env,
+    actor: Option<Addr>,
+    key: PublicKey,
+    action: &str,
+) -> Result<Response, ContractError> {
+    if !is_whitelisted(&deps.as_ref(), &key) {
         return Err(ContractError::KeyNotWhitelisted {});
     }
-    let key_info = WHITELISTED_KEYS.load(deps.storage, data.key.as_bytes())?;
-    WHITELISTED_KEYS.remove(deps.storage, data.key.as_bytes());
+    let key_info = WHITELISTED_KEYS.load(deps.storage, key.as_bytes())?;
+    WHITELISTED_KEYS.remove(deps.storage, key.as_bytes());
+    let denom = &key_info.deposit_denom;
+    let outgoing = key_info.deposit_amount;
+    update_ledger_for_payout(deps.branch(), env, denom, outgoing, |ledger| {
+        ledger.deposits = ledger.deposits.checked_sub(key_info.deposit_amount)?;
+        Ok(())
+    })?;
+
+    let field = format!("whitelisted_keys.{}", key);
+    let changes = vec![audit_change(field, &Some(&key_info), &None)?];
+    record_audit(deps.storage, env, actor, action, changes)?;
+
+    let refund = Coin {
+        denom: key_info.deposit_denom.clone(),
+        amount: key_info.deposit_amount,
+    };
+    Ok(Response::new()
+        .add_messages(send_coins(key_info.payout_address(), vec![refund])?)
+        .add_attribute("action", action)
+        .add_attribute("unwhitelisted_key", format!("{}", key))
+        .add_attribute("refund", format!("{}", key_info.deposit_amount)))
+}
+
+/// Update the configuration of the contract
+/// This is only allowed to be called by the owner
+///
+/// Pausing and unpausing always takes effect immediately, and sets every
+/// pause flag at once. Every other change is applied immediately only if
+/// `config_timelock` is zero, and is otherwise scheduled to be applied once
+/// the timelock has passed.
+pub fn update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: UpdateConfigMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    check_owner(&cfg, &info.sender)?;
+    let old_cfg = cfg.clone();
+
+    if let Some(paused) = data.paused {
+        cfg.pause_flags = PauseFlags::all(paused);
+    }
+    let update = UpdateConfigMsg {
+        paused: None,
+        ..data
+    };
+
+    let mut response = Response::new().add_attribute("action", "update_config");
+    let mut scheduled = None;
+    if cfg.config_timelock == 0 {
+        cfg.apply_update(update);
+        cfg.validate()?;
+        check_native_denom_change(deps.storage, &old_cfg, &cfg)?;
+    } else if update != empty_config_update() {
+        let mut scheduled_cfg = cfg.clone();
+        scheduled_cfg.apply_update(update.clone());
+        scheduled_cfg.validate()?;
+        check_native_denom_change(deps.storage, &cfg, &scheduled_cfg)?;
+
+        let change = schedule_config_change(deps.storage, &env, &cfg, update, None)?;
+        response = response
+            .add_attribute("scheduled_change_id", change.id.to_string())
+            .add_attribute("effective_height", change.effective_height.to_string());
+        scheduled = Some(change);
+    }
+
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let mut changes = config_changes(&old_cfg, &cfg)?;
+    if let Some(change) = scheduled {
+        let field = format!("pending_config_changes.{}", change.id);
+        changes.push(audit_change(field, &None, &Some(change))?);
+    }
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "update_config",
+        changes,
+    )?;
+
+    Ok(response)
+}
+
+/// Updates the number of blocks that config changes must wait before being
+/// applied. Lengthening the timelock takes effect immediately, while
+/// shortening it is itself subject to the current timelock.
+/// This is only allowed to be called by the owner.
+pub fn update_config_timelock(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: UpdateConfigTimelockMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let response = Response::new()
+        .add_attribute("action", "update_config_timelock")
+        .add_attribute("config_timelock", data.blocks.to_string());
+
+    if data.blocks >= cfg.config_timelock {
+        let old_cfg = cfg.clone();
+        cfg.config_timelock = data.blocks;
+        CONFIG.save(deps.storage, &cfg)?;
+
+        let changes = config_changes(&old_cfg, &cfg)?;
+        record_audit(
+            deps.storage,
+            &env,
+            Some(info.sender),
+            "update_config_timelock",
+            changes,
+        )?;
+        return Ok(response);
+    }
+
+    let change = schedule_config_change(
+        deps.storage,
+        &env,
+        &cfg,
+        empty_config_update(),
+        Some(data.blocks),
+    )?;
+
+    let field = format!("pending_config_changes.{}", change.id);
+    let changes = vec![audit_change(field, &None, &Some(&change))?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "update_config_timelock",
+        changes,
+    )?;
+
+    Ok(response
+        .add_attribute("scheduled_change_id", change.id.to_string())
+        .add_attribute("effective_height", change.effective_height.to_string()))
+}
+
+/// Applies a scheduled config change whose timelock has passed.
+/// Can be called by anyone. Fails if the change would leave the config
+/// invalid, for example because of another change applied in the meantime.
+pub fn apply_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: ApplyConfigChangeMsg,
+) -> Result<Response, ContractError> {
+    let change = load_config_change(deps.storage, data.id)?;
+    if env.block.height < change.effective_height {
+        return Err(ContractError::ConfigChangeNotReady {
+            effective_height: change.effective_height,
+        });
+    }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let old_cfg = cfg.clone();
+    cfg.apply_update(change.update.clone());
+    cfg.config_timelock = change.config_timelock.unwrap_or(cfg.config_timelock);
+    cfg.validate()?;
+    check_native_denom_change(deps.storage, &old_cfg, &cfg)?;
+    CONFIG.save(deps.storage, &cfg)?;
+    PENDING_CONFIG_CHANGES.remove(deps.storage, data.id);
+
+    let mut changes = config_changes(&old_cfg, &cfg)?;
+    let field = format!("pending_config_changes.{}", data.id);
+    changes.push(audit_change(field, &Some(change), &None)?);
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "apply_config_change",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "apply_config_change")
+        .add_attribute("change_id", data.id.to_string()))
+}
+
+/// Cancels a scheduled config change before it is applied.
+/// This is only allowed to be called by the owner.
+pub fn cancel_config_change(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: CancelConfigChangeMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let change = load_config_change(deps.storage, data.id)?;
+    PENDING_CONFIG_CHANGES.remove(deps.storage, data.id);
+
+    let field = format!("pending_config_changes.{}", data.id);
+    let changes = vec![audit_change(field, &Some(change), &None)?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "cancel_config_change",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_config_change")
+        .add_attribute("change_id", data.id.to_string()))
+}
+
+/// Permanently takes the contract out of test mode, so that keys and proofs
+/// are verified from then on. Test mode can never be re-entered.
+/// This is only allowed to be called by the owner, and only once every
+/// request that was made under test mode has been fulfilled.
+pub fn exit_test_mode(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _data: ExitTestModeMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    if !cfg.test_mode {
+        return Err(ContractError::NotInTestMode {});
+    }
+    if !ENTROPY_REQUESTS.is_empty(deps.storage) {
+        return Err(ContractError::PendingTestModeRequests {});
+    }
+
+    let old_cfg = cfg.clone();
+    cfg.test_mode = false;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender.clone()),
+        "exit_test_mode",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "exit_test_mode")
+        .add_event(
+            Event::new("test_mode_exited")
+                .add_attribute("owner", info.sender)
+                .add_attribute("height", env.block.height.to_string()),
+        ))
+}
+
+/// Permanently starts winding down the beacon. New requests and whitelisting
+/// are refused from then on, and anyone can call `process_sunset` to refund
+/// every pending request and key deposit.
+/// This is only allowed to be called by the owner.
+pub fn start_sunset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    _data: StartSunsetMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+
+    let old_cfg = cfg.clone();
+    cfg.sunset = true;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender.clone()),
+        "start_sunset",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "start_sunset")
+        .add_event(
+            Event::new("sunset_started")
+                .add_attribute("owner", info.sender)
+                .add_attribute("height", env.block.height.to_string()),
+        ))
+}
+
+/// Refunds a batch of pending requests and key deposits while the beacon is
+/// being wound down. Pending requests are refunded first, oldest first, with
+/// their whole bounty going back to the requester. Whitelisted keys follow,
+/// each returning its entire deposit to the key holder.
+/// Can be called by anyone, until the beacon holds no requests or keys.
+pub fn process_sunset(
+    mut deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    data: ProcessSunsetMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if !cfg.sunset {
+        return Err(ContractError::NotInSunset {});
+    }
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT) as usize;
+
+    let requests = ENTROPY_REQUESTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    STATE.update(deps.storage, |mut state| -> StdResult<_> {
+        state.pending_requests = state.pending_requests.saturating_sub(requests.len() as u64);
+        Ok(state)
+    })?;
+    let keys = WHITELISTED_KEYS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit - requests.len())
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let refund = |to_address: &Addr, denom: &str, amount: Uint128| {
+        if amount.is_zero() {
+            return Ok(vec![]);
+        }
+        let coin = Coin {
+            denom: denom.to_string(),
+            amount,
+        };
+        send_coins(to_address, vec![coin])
+    };
+
+    let mut messages = vec![];
+    // What is refunded from the ledger of each denom.
+    let mut refunded: BTreeMap<String, Ledger> = BTreeMap::new();
+    for (id, req) in &requests {
+        ENTROPY_REQUESTS.remove(deps.storage, *id);
+        // Whatever the sponsorship pool paid for goes back to the pool.
+        SPONSORSHIP_SPENDS.remove(deps.storage, *id);
+        let refund_entry = refunded.entry(req.denom.clone()).or_default();
+        refund_entry.bounties += req.submitted_bounty_amount;
+        refund_entry.sponsorship += req.subsidy;
+        let amount = req.submitted_bounty_amount - req.subsidy;
+        // Bounties paid from a fee grant go back to the grant, or to its
+        // sponsor if it has been revoked since.
+        let to_address = match &req.fee_grant {
+            Some(key) => {
+                let grant_key = (&key.grantee, &key.sponsor, req.denom.as_str());
+                match FEE_GRANTS.may_load(deps.storage, grant_key)? {
+                    Some(mut grant) => {
+                        grant.balance += amount;
+                        grant.spent = grant.spent.saturating_sub(amount);
+                        FEE_GRANTS.save(deps.storage, grant_key, &grant)?;
+                        refund_entry.grants += amount;
+                        continue;
+                    }
+                    None => &key.sponsor,
+                }
+            }
+            None => &req.submitter,
+        };
+        messages.extend(refund(to_address, &req.denom, amount)?);
+    }
+    for (key, key_info) in &keys {
+        WHITELISTED_KEYS.remove(deps.storage, key);
+        let amount = key_info.deposit_amount;
+        let denom = &key_info.deposit_denom;
+        refunded.entry(denom.clone()).or_default().deposits += amount;
+        messages.extend(refund(key_info.payout_address(), denom, amount)?);
+    }
+    for (denom, refund) in &refunded {
+        let outgoing = refund.bounties + refund.deposits - refund.sponsorship - refund.grants;
+        update_ledger_for_payout(deps.branch(), &env, denom, outgoing, |ledger| {
+            ledger.bounties = ledger.bounties.checked_sub(refund.bounties)?;
+            ledger.deposits = ledger.deposits.checked_sub(refund.deposits)?;
+            ledger.sponsorship += refund.sponsorship;
+            ledger.grants += refund.grants;
+            Ok(())
+        })?;
+    }
+
+    let complete =
+        ENTROPY_REQUESTS.is_empty(deps.storage) && WHITELISTED_KEYS.is_empty(deps.storage);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "process_sunset")
+        .add_attribute("refunded_requests", requests.len().to_string())
+        .add_attribute("returned_deposits", keys.len().to_string())
+        .add_attribute("complete", complete.to_string()))
+}
+
+/// Sends back coins of denoms that the beacon does not accept.
+pub(crate) fn refund_foreign(to: &Addr, coins: Vec<Coin>) -> Option<BankMsg> {
+    if coins.is_empty() {
+        return None;
+    }
+    Some(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins,
+    })
+}
+
+/// Sets whether coins of denoms other than the native denom are rejected or
+/// refunded. This is only allowed to be called by the owner.
+pub fn set_foreign_denom_policy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetForeignDenomPolicyMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let old_cfg = cfg.clone();
+    cfg.foreign_denoms = data.policy;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_foreign_denom_policy",
+        changes,
+    )?;
+
+    Ok(Response::new().add_attribute("action", "set_foreign_denom_policy"))
+}
+
+/// Lets the believed gas price follow demand, or stops it from doing so.
+/// This is only allowed to be called by the owner.
+pub fn set_gas_price_adjustment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetGasPriceAdjustmentMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let old_cfg = cfg.clone();
+    cfg.gas_price_adjustment = data.adjustment;
+    cfg.validate()?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_gas_price_adjustment",
+        changes,
+    )?;
+
+    Ok(Response::new().add_attribute("action", "set_gas_price_adjustment"))
+}
+
+/// Designates the beacon that pending requests are handed off to.
+/// This is only allowed to be called by the owner.
+pub fn set_successor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetSuccessorMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let old_cfg = cfg.clone();
+    cfg.successor = data
+        .address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_successor",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_successor")
+        .add_attribute(
+            "successor",
+            cfg.successor.map(String::from).unwrap_or_default(),
+        ))
+}
+
+/// Designates the beacon that is allowed to hand off its pending requests
+/// to this one. This is only allowed to be called by the owner.
+pub fn set_predecessor(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetPredecessorMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+
+    let old_cfg = cfg.clone();
+    cfg.predecessor = data
+        .address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_predecessor",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_predecessor")
+        .add_attribute(
+            "predecessor",
+            cfg.predecessor.map(String::from).unwrap_or_default(),
+        ))
+}
+
+/// Hands off a batch of pending requests, oldest first, to the successor
+/// beacon. The requests are removed here, and their bounties are sent along
+/// so that the successor can pay out whoever fulfills them.
+/// This is only allowed to be called by the owner.
+pub fn forward_requests(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: ForwardRequestsMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+    let successor = cfg.successor.clone().ok_or(ContractError::NoSuccessor {})?;
+
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT) as usize;
+
+    let requests = ENTROPY_REQUESTS
+        .range(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    STATE.update(deps.storage, |mut state| -> StdResult<_> {
+        state.pending_requests = state.pending_requests.saturating_sub(requests.len() as u64);
+        Ok(state)
+    })?;
+
+    let mut changes = vec![];
+    let mut forwarded = vec![];
+    // What is forwarded from the ledger of each denom.
+    let mut removed: BTreeMap<String, Ledger> = BTreeMap::new();
+    for (id, req) in requests {
+        ENTROPY_REQUESTS.remove(deps.storage, id);
+        changes.push(audit_change(
+            format!("entropy_requests.{}", id),
+            &Some(&req),
+            &None,
+        )?);
+        // Only the requester's part of the bounty is forwarded, and whatever
+        // the sponsorship pool paid for goes back to the pool.
+        SPONSORSHIP_SPENDS.remove(deps.storage, id);
+        let entry = removed.entry(req.denom.clone()).or_default();
+        entry.bounties += req.submitted_bounty_amount;
+        entry.sponsorship += req.subsidy;
+        forwarded.push(ForwardedRequest {
+            requester: req.submitter,
+            callback_gas_limit: req.callback_gas_limit,
+            callback_address: req.callback_address,
+            callback_msg: req.callback_msg,
+            bounty: req.submitted_bounty_amount - req.subsidy,
+            denom: req.denom,
+            referrer: req.referrer,
+            fee_grant: req.fee_grant,
+        });
+    }
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "forward_requests",
+        changes,
+    )?;
+
+    let response = Response::new()
+        .add_attribute("action", "forward_requests")
+        .add_attribute("successor", successor.as_str())
+        .add_attribute("forwarded_requests", forwarded.len().to_string());
+    if forwarded.is_empty() {
+        return Ok(response);
+    }
+
+    let mut funds = vec![];
+    for req in &forwarded {
+        add_coin(&mut funds, &req.denom, req.bounty);
+    }
+    for (denom, removed) in &removed {
+        let outgoing = removed.bounties - removed.sponsorship;
+        update_ledger_for_payout(deps.branch(), &env, denom, outgoing, |ledger| {
+            ledger.bounties = ledger.bounties.checked_sub(removed.bounties)?;
+            ledger.sponsorship += removed.sponsorship;
+            Ok(())
+        })?;
+    }
+
+    // Cw20 bounties are transferred ahead of the requests, as they cannot be
+    // attached to them.
+    let (tokens, funds): (Vec<_>, Vec<_>) = funds
+        .into_iter()
+        .partition(|c| cw20_token(&c.denom).is_some());
+    Ok(response
+        .add_messages(send_coins(&successor, tokens)?)
+        .add_message(WasmMsg::Execute {
+            contract_addr: successor.into_string(),
+            msg: to_binary(&ExecuteMsg::ReceiveForwardedRequests(
+                ReceiveForwardedRequestsMsg {
+                    requests: forwarded,
+                },
+            ))?,
+            funds,
+        }))
+}
+
+/// Re-creates the requests handed off by the predecessor beacon, keeping
+/// their original requester, callback and bounty.
+/// This is only allowed to be called by the predecessor, which must send
+/// along the bounties of every request, and is refunded anything sent beyond
+/// them. Bounties in cw20 tokens must have been transferred beforehand.
+pub fn receive_forwarded_requests(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: ReceiveForwardedRequestsMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.predecessor.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+
+    let mut bounties = vec![];
+    for req in &data.requests {
+        add_coin(&mut bounties, &req.denom, req.bounty);
+    }
+    let (received, mut refund) = split_funds(&cfg, &info.funds, |denom| {
+        bounties.iter().any(|c| c.denom == denom)
+    })?;
+    for bounty in bounties.iter().filter(|c| cw20_token(&c.denom).is_none()) {
+        let received_amt = received
+            .iter()
+            .find(|c| c.denom == bounty.denom)
+            .map(|c| c.amount)
+            .unwrap_or_default();
+        if received_amt < bounty.amount {
+            return Err(ContractError::InsufficientFunds {});
+        }
+        if received_amt > bounty.amount {
+            add_coin(&mut refund, &bounty.denom, received_amt - bounty.amount);
+        }
+    }
+
+    for bounty in &bounties {
+        let ledger = update_ledger(deps.storage, &bounty.denom, |ledger| {
+            ledger.bounties += bounty.amount;
+            Ok(())
+        })?;
+        if cw20_token(&bounty.denom).is_some()
+            && balance(&deps.as_ref(), &env, &bounty.denom)? < ledger.liabilities()
+        {
+            return Err(ContractError::InsufficientFunds {});
+        }
+    }
+    let mut state = STATE.load(deps.storage)?;
+    let first_id = state.cur_request_id;
+    for req in data.requests {
+        let request = EntropyRequest {
+            id: state.cur_request_id,
+            callback_gas_limit: req.callback_gas_limit,
+            callback_address: req.callback_address,
+            callback_msg: req.callback_msg,
+            submitter: req.requester,
+            submitted_block_height: env.block.height,
+            submitted_bounty_amount: req.bounty,
+            subsidy: Uint128::zero(),
+            denom: req.denom,
+            referrer: req.referrer,
+            fee_grant: req.fee_grant,
+        };
+        ENTROPY_REQUESTS.save(deps.storage, request.id, &request)?;
+        state.cur_request_id += 1;
+        state.pending_requests += 1;
+    }
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new()
+        .add_messages(refund_foreign(&info.sender, refund))
+        .add_attribute("action", "receive_forwarded_requests")
+        .add_attribute("first_request_id", first_id.to_string())
+        .add_attribute(
+            "received_requests",
+            (state.cur_request_id - first_id).to_string(),
+        ))
+}
+
+/// Imports a page of state exported from another beacon, overwriting the
+/// `State`, `Config`, roles, pending owner, pending config changes and the
+/// revenue and sponsorship of each ledger, and adding the exported keys,
+/// pending requests, rewards, credits, fee grants, sponsorship spends and gas
+/// price history.
+/// The owner of this beacon is kept, so that importing can continue.
+/// This is only allowed to be called by the owner, and only until the owner
+/// finalizes the import.
+pub fn import_state(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: ImportStateMsg,
+) -> Result<Response, ContractError> {
+    let old_cfg = CONFIG.load(deps.storage)?;
+    check_owner(&old_cfg, &info.sender)?;
+    if !IMPORT_OPEN.may_load(deps.storage)?.unwrap_or(false) {
+        return Err(ContractError::ImportClosed {});
+    }
+
+    let export = *data.export;
+    if export.version != STATE_EXPORT_VERSION {
+        return Err(ContractError::UnsupportedExportVersion {
+            version: export.version,
+        });
+    }
+
+    let cfg = Config {
+        owner: old_cfg.owner.clone(),
+        ..export.config
+    };
+    cfg.validate()?;
+    CONFIG.save(deps.storage, &cfg)?;
+    STATE.save(deps.storage, &export.state)?;
+
+    let old_denoms = FEE_DENOMS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for denom in old_denoms {
+        FEE_DENOMS.remove(deps.storage, &denom);
+    }
+    for fee_denom in &export.fee_denoms {
+        FEE_DENOMS.save(deps.storage, &fee_denom.denom, &fee_denom.pricing)?;
+    }
+
+    // Pages may overlap, so anything that is imported again replaces what
+    // was owed for it before.
+    let mut owed: BTreeMap<String, Ledger> = BTreeMap::new();
+    let mut replaced: BTreeMap<String, Ledger> = BTreeMap::new();
+    for key in &export.keys {
+        if key.public_key.validate().is_err() {
+            return Err(ContractError::InvalidPublicKey {});
+        }
+        let old = WHITELISTED_KEYS.may_load(deps.storage, key.public_key.as_bytes())?;
+        if let Some(old) = old {
+            replaced.entry(old.deposit_denom).or_default().deposits += old.deposit_amount;
+        }
+        let denom = key.info.deposit_denom.clone();
+        owed.entry(denom).or_default().deposits += key.info.deposit_amount;
+        WHITELISTED_KEYS.save(deps.storage, key.public_key.as_bytes(), &key.info)?;
+    }
+    for req in &export.requests {
+        let old = ENTROPY_REQUESTS.may_load(deps.storage, req.id)?;
+        if let Some(old) = old {
+            replaced.entry(old.denom).or_default().bounties += old.submitted_bounty_amount;
+        }
+        owed.entry(req.denom.clone()).or_default().bounties += req.submitted_bounty_amount;
+        ENTROPY_REQUESTS.save(deps.storage, req.id, req)?;
+    }
+    for reward in &export.rewards {
+        let key = (&reward.address, reward.denom.as_str());
+        if let Some(old) = REWARDS.may_load(deps.storage, key)? {
+            replaced.entry(reward.denom.clone()).or_default().rewards += old;
+        }
+        owed.entry(reward.denom.clone()).or_default().rewards += reward.amount;
+        REWARDS.save(deps.storage, key, &reward.amount)?;
+    }
+    for credit in &export.credits {
+        let key = (&credit.address, credit.denom.as_str());
+        if let Some(old) = CREDITS.may_load(deps.storage, key)? {
+            replaced.entry(credit.denom.clone()).or_default().credits += old;
+        }
+        owed.entry(credit.denom.clone()).or_default().credits += credit.amount;
+        CREDITS.save(deps.storage, key, &credit.amount)?;
+    }
+    for grant in &export.grants {
+        let key = (&grant.grantee, &grant.sponsor, grant.denom.as_str());
+        if let Some(old) = FEE_GRANTS.may_load(deps.storage, key)? {
+            replaced.entry(old.denom).or_default().grants += old.balance;
+        }
+        owed.entry(grant.denom.clone()).or_default().grants += grant.balance;
+        FEE_GRANTS.save(deps.storage, key, grant)?;
+    }
+    let denoms: BTreeSet<_> = owed.keys().chain(replaced.keys()).cloned().collect();
+    for denom in denoms {
+        let owed = owed.remove(&denom).unwrap_or_default();
+        let replaced = replaced.remove(&denom).unwrap_or_default();
+        update_ledger(deps.storage, &denom, |ledger| {
+            ledger.deposits = (ledger.deposits + owed.deposits).checked_sub(replaced.deposits)?;
+            ledger.bounties = (ledger.bounties + owed.bounties).checked_sub(replaced.bounties)?;
+            ledger.rewards = (ledger.rewards + owed.rewards).checked_sub(replaced.rewards)?;
+            ledger.credits = (ledger.credits + owed.credits).checked_sub(replaced.credits)?;
+            ledger.grants = (ledger.grants + owed.grants).checked_sub(replaced.grants)?;
+            Ok(())
+        })?;
+    }
+    // Revenue and the sponsorship pool are not owed to any exported entry, so
+    // every page carries them in full.
+    for exported in &export.ledgers {
+        update_ledger(deps.storage, &exported.denom, |ledger| {
+            ledger.revenue = exported.ledger.revenue;
+            ledger.sponsorship = exported.ledger.sponsorship;
+            Ok(())
+        })?;
+    }
+
+    // Every page carries the roles, pending owner and pending config changes
+    // in full.
+    let old_roles = ROLES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (role, address) in old_roles {
+        ROLES.remove(deps.storage, (&role, &address));
+    }
+    for exported in &export.roles {
+        let key = (exported.role.as_str(), &exported.address);
+        ROLES.save(deps.storage, key, &Empty {})?;
+    }
+    match &export.pending_owner {
+        Some(pending_owner) => PENDING_OWNER.save(deps.storage, pending_owner)?,
+        None => PENDING_OWNER.remove(deps.storage),
+    }
+    let old_changes = PENDING_CONFIG_CHANGES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for id in old_changes {
+        PENDING_CONFIG_CHANGES.remove(deps.storage, id);
+    }
+    for change in &export.pending_config_changes {
+        PENDING_CONFIG_CHANGES.save(deps.storage, change.id, change)?;
+    }
+    for spend in &export.sponsorship_spends {
+        SPONSORSHIP_SPENDS.save(deps.storage, spend.request_id.u128(), spend)?;
+    }
+    for record in &export.gas_price_history {
+        GAS_PRICE_HISTORY.save(deps.storage, record.id, record)?;
+    }
+
+    if data.finalize {
+        IMPORT_OPEN.remove(deps.storage);
+    }
+
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "import_state",
+        changes,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "import_state")
+        .add_attribute("imported_keys", export.keys.len().to_string())
+        .add_attribute("imported_requests", export.requests.len().to_string())
+        .add_attribute("imported_rewards", export.rewards.len().to_string())
+        .add_attribute("imported_credits", export.credits.len().to_string())
+        .add_attribute("imported_grants", export.grants.len().to_string())
+        .add_attribute("finalized", data.finalize.to_string()))
+}
+
+/// Withdraws accrued protocol revenue in the native denom. Only revenue can
+/// be withdrawn, so key deposits and pending bounties are never touched.
+/// This is only allowed to be called by a holder of the `Treasurer` role.
+pub fn withdraw_revenue(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: WithdrawRevenueMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+    check_role(&deps.as_ref(), &cfg, Role::Treasurer, &info.sender)?;
+
+    let recipient = match data.recipient {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => info.sender.clone(),
+    };
+
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let available = LEDGERS
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default()
+        .revenue;
+    let amount = data.amount.unwrap_or(available);
+    if amount > available {
+        return Err(ContractError::InsufficientRevenue { available });
+    }
+    let remaining = available - amount;
+    update_ledger_for_payout(deps.branch(), &env, &denom, amount, |ledger| {
+        ledger.revenue = remaining;
+        Ok(())
+    })?;
+
+    let field = format!("ledgers.{}.revenue", denom);
+    let changes = vec![audit_change(field, &available, &remaining)?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "withdraw_revenue",
+        changes,
+    )?;
+
+    let response = Response::new()
+        .add_attribute("action", "withdraw_revenue")
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("amount", amount.to_string());
+    if amount.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_messages(send_coins(&recipient, vec![Coin { denom, amount }])?))
+}
+
+/// Pays out rewards that an address has accrued through the keys paying out to
+/// it, either in full or in part. Rewards are only ever paid to the address
+/// that accrued them, so anyone may claim them on its behalf, such as the
+/// holder of the keys claiming for a cold wallet.
+pub fn claim_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: ClaimRewardsMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+
+    let recipient = match data.address {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => info.sender,
+    };
+
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let available = REWARDS
+        .may_load(deps.storage, (&recipient, &denom))?
+        .unwrap_or_default();
+    let amount = data.amount.unwrap_or(available);
+    if amount > available {
+        return Err(ContractError::InsufficientRewards { available });
+    }
+    let remaining = available - amount;
+    if remaining.is_zero() {
+        REWARDS.remove(deps.storage, (&recipient, &denom));
+    } else {
+        REWARDS.save(deps.storage, (&recipient, &denom), &remaining)?;
+    }
+    update_ledger_for_payout(deps.branch(), &env, &denom, amount, |ledger| {
+        ledger.rewards = ledger.rewards.checked_sub(amount)?;
+        Ok(())
+    })?;
+
+    let response = Response::new()
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("amount", amount.to_string());
+    if amount.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_messages(send_coins(&recipient, vec![Coin { denom, amount }])?))
+}
+
+/// Accepts fees in a denom other than the native denom, updates its price, or
+/// stops accepting it. Requests that were already paid in a denom are still
+/// paid out in it, but keys with deposits in a denom that no longer accepts
+/// deposits stop unlocking them. This is only allowed to be called by the owner.
+pub fn set_fee_denom(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: SetFeeDenomMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
+    if data.denom.is_empty() || data.denom == cfg.native_denom {
+        return Err(ContractError::InvalidFeeDenom {});
+    }
+    if let Some(token) = cw20_token(&data.denom) {
+        if deps.api.addr_validate(token).is_err() {
+            return Err(ContractError::InvalidFeeDenom {});
+        }
+    }
+    if let Some(deposit) = data.pricing.as_ref().and_then(|p| p.deposit.as_ref()) {
+        if deposit.refund_increment_amt > deposit.whitelist_deposit_amt {
+            return Err(ContractError::RefundIncrementExceedsDeposit {});
+        }
+    }
+
+    let old = FEE_DENOMS.may_load(deps.storage, &data.denom)?;
+    match &data.pricing {
+        Some(pricing) => FEE_DENOMS.save(deps.storage, &data.denom, pricing)?,
+        None => FEE_DENOMS.remove(deps.storage, &data.denom),
+    }
+
+    let field = format!("fee_denoms.{}", data.denom);
+    let changes = vec![audit_change(field, &old, &data.pricing)?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_fee_denom",
+        changes,
+    )?;
+
     Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: key_info.holder.to_string(),
-            amount: vec![Coin {
-                denom: cfg.native_denom,
-                amount: key_info.deposit_amount,
-            }],
-        }))
-        .add_attribute("action", "reclaim_deposit")
-        .add_attribute("unwhitelisted_key", format!("{}", data.key))
-        .add_attribute("refund", format!("{}", key_info.deposit_amount)))
+        .add_attribute("action", "set_fee_denom")
+        .add_attribute("denom", data.denom))
 }
 
-/// Update the configuration of the contract
-/// This is only allowed to be called by the owner
-pub fn update_config(
+/// Sets how the bounty of each fulfilled request is split.
+/// This is only allowed to be called by the owner.
+pub fn set_fee_split(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    data: UpdateConfigMsg,
+    data: SetFeeSplitMsg,
 ) -> Result<Response, ContractError> {
     let mut cfg = CONFIG.load(deps.storage)?;
+    check_owner(&cfg, &info.sender)?;
 
-    if cfg.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    for share in data.shares.iter().flatten() {
+        if let FeeRecipient::Treasury { address } = &share.recipient {
+            deps.api.addr_validate(address.as_str())?;
+        }
     }
 
-    cfg.whitelist_deposit_amt = data
-        .whitelist_deposit_amt
-        .unwrap_or(cfg.whitelist_deposit_amt);
-    cfg.refund_increment_amt = data
-        .refund_increment_amt
-        .unwrap_or(cfg.refund_increment_amt);
-    cfg.key_activation_delay = data
-        .key_activation_delay
-        .unwrap_or(cfg.key_activation_delay);
-    cfg.protocol_fee = data.protocol_fee.unwrap_or(cfg.protocol_fee);
-    cfg.submitter_share = data
-        .submitter_share
-        .map(Decimal::percent)
-        .unwrap_or(cfg.submitter_share);
-    cfg.native_denom = data.native_denom.unwrap_or(cfg.native_denom);
-    cfg.paused = data.paused.unwrap_or(cfg.paused);
-    cfg.permissioned = data.permissioned.unwrap_or(cfg.permissioned);
-    cfg.subsidize_callbacks = data.subsidize_callbacks.unwrap_or(cfg.subsidize_callbacks);
-
+    let old_cfg = cfg.clone();
+    cfg.fee_split = data.shares;
+    cfg.validate()?;
     CONFIG.save(deps.storage, &cfg)?;
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    let changes = config_changes(&old_cfg, &cfg)?;
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "set_fee_split",
+        changes,
+    )?;
+
+    Ok(Response::new().add_attribute("action", "set_fee_split"))
+}
+
+fn schedule_config_change(
+    storage: &mut dyn Storage,
+    env: &Env,
+    cfg: &Config,
+    update: UpdateConfigMsg,
+    config_timelock: Option<u64>,
+) -> Result<PendingConfigChange, ContractError> {
+    let mut state = STATE.load(storage)?;
+    let change = PendingConfigChange {
+        id: state.cur_config_change_id,
+        update,
+        config_timelock,
+        scheduled_height: env.block.height,
+        effective_height: env.block.height + cfg.config_timelock,
+    };
+    PENDING_CONFIG_CHANGES.save(storage, change.id, &change)?;
+
+    state.cur_config_change_id += 1;
+    STATE.save(storage, &state)?;
+    Ok(change)
+}
+
+fn load_config_change(
+    storage: &dyn Storage,
+    id: u64,
+) -> Result<PendingConfigChange, ContractError> {
+    PENDING_CONFIG_CHANGES
+        .may_load(storage, id)?
+        .ok_or(ContractError::NoMatchingConfigChange { change_id: id })
 }
 
 /// Whitelists a public key, noting down the block height.
@@ -116,11 +1457,47 @@ pub fn whitelist_key(
     data: WhitelistPublicKeyMsg,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.paused {
+
+    // The deposit is paid in the first denom sent that deposits are accepted in.
+    let mut denom = cfg.native_denom.clone();
+    for coin in info.funds.iter().filter(|c| !c.amount.is_zero()) {
+        if deposit_terms(deps.storage, &cfg, &coin.denom)?.is_some() {
+            denom = coin.denom.clone();
+            break;
+        }
+    }
+    let (amount, foreign) = received_funds(&cfg, &info.funds, &denom)?;
+
+    deposit_for_key(
+        deps,
+        env,
+        cfg,
+        info.sender,
+        Coin { denom, amount },
+        foreign,
+        data,
+    )
+}
+
+/// Whitelists a key with a deposit that has already been received, and
+/// refunds whatever was sent beyond it.
+fn deposit_for_key(
+    deps: DepsMut,
+    env: Env,
+    cfg: Config,
+    sender: Addr,
+    received: Coin,
+    foreign: Vec<Coin>,
+    data: WhitelistPublicKeyMsg,
+) -> Result<Response, ContractError> {
+    if cfg.pause_flags.whitelisting {
         return Err(ContractError::Paused {});
     }
-    if cfg.permissioned && cfg.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+    if cfg.permissioned {
+        check_role(&deps.as_ref(), &cfg, Role::KeyManager, &sender)?;
     }
 
     let key = data.public_key;
@@ -132,32 +1509,41 @@ pub fn whitelist_key(
         return Err(ContractError::KeyAlreadyWhitelisted {});
     }
 
-    let received_funds_amt: Uint128 = info
-        .funds
-        .iter()
-        .filter_map(|coin| {
-            if coin.denom == cfg.native_denom {
-                Some(coin.amount)
-            } else {
-                None
-            }
-        })
-        .sum();
-    if received_funds_amt < cfg.whitelist_deposit_amt {
+    let terms = deposit_terms(deps.storage, &cfg, &received.denom)?.ok_or_else(|| {
+        ContractError::UnsupportedDenom {
+            denom: received.denom.clone(),
+        }
+    })?;
+    let deposit_amount = terms.whitelist_deposit_amt;
+    if received.amount < deposit_amount {
         return Err(ContractError::InsufficientFunds {});
     }
+    let mut refund = vec![];
+    add_coin(
+        &mut refund,
+        &received.denom,
+        received.amount - deposit_amount,
+    );
 
+    update_ledger(deps.storage, &received.denom, |ledger| {
+        ledger.deposits += deposit_amount;
+        Ok(())
+    })?;
     WHITELISTED_KEYS.save(
         deps.storage,
         key.as_bytes(),
         &KeyInfo {
             creation_height: env.block.height,
-            deposit_amount: cfg.whitelist_deposit_amt,
+            deposit_amount,
             refundable_amount: Uint128::zero(),
-            holder: info.sender,
+            holder: sender.clone(),
+            payout_address: None,
+            deposit_denom: received.denom,
         },
     )?;
     Ok(Response::new()
+        .add_messages(refund_foreign(&sender, foreign))
+        .add_messages(send_coins(&sender, refund)?)
         .add_attribute("action", "whitelist_public_key")
         .add_attribute("public_key", format!("{}", data.public_key))
         .add_attribute(
@@ -166,16 +1552,48 @@ pub fn whitelist_key(
         ))
 }
 
+/// Allows the holder of a public key to choose where its rewards and deposit
+/// refunds are sent, or to have them sent to the holder again.
+pub fn set_payout_address(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data: SetPayoutAddressMsg,
+) -> Result<Response, ContractError> {
+    let key = data.public_key;
+    if !is_whitelisted(&deps.as_ref(), &key) {
+        return Err(ContractError::KeyNotWhitelisted {});
+    }
+    let mut key_info = WHITELISTED_KEYS.load(deps.storage, key.as_bytes())?;
+    if info.sender != key_info.holder {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    key_info.payout_address = data
+        .address
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    WHITELISTED_KEYS.save(deps.storage, key.as_bytes(), &key_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_payout_address")
+        .add_attribute("public_key", format!("{}", key))
+        .add_attribute("payout_address", key_info.payout_address().as_str()))
+}
+
 /// Allows the holder of a public key to unwhitelist it, and reclaim the
 /// deposit that was made when the key was whitelisted.
+/// The part of the deposit that has not been unlocked yet is forfeited to
+/// the protocol. While the beacon is being wound down, the entire deposit
+/// is returned.
 pub fn reclaim_deposit(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     data: ReclaimDepositMsg,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.paused {
+    if cfg.pause_flags.withdrawals {
         return Err(ContractError::Paused {});
     }
     let key = data.public_key;
@@ -191,16 +1609,24 @@ pub fn reclaim_deposit(
 
     WHITELISTED_KEYS.remove(deps.storage, key.as_bytes());
 
-    let refund_amt = key_info.refundable_amount.min(key_info.deposit_amount);
+    let refund_amt = if cfg.sunset {
+        key_info.deposit_amount
+    } else {
+        key_info.refundable_amount.min(key_info.deposit_amount)
+    };
+    let denom = &key_info.deposit_denom;
+    update_ledger_for_payout(deps.branch(), &env, denom, refund_amt, |ledger| {
+        ledger.deposits = ledger.deposits.checked_sub(key_info.deposit_amount)?;
+        ledger.revenue += key_info.deposit_amount - refund_amt;
+        Ok(())
+    })?;
 
+    let refund = Coin {
+        denom: denom.clone(),
+        amount: refund_amt,
+    };
     Ok(Response::new()
-        .add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: key_info.holder.to_string(),
-            amount: vec![Coin {
-                denom: cfg.native_denom,
-                amount: refund_amt,
-            }],
-        }))
+        .add_messages(send_coins(key_info.payout_address(), vec![refund])?)
         .add_attribute("action", "reclaim_deposit")
         .add_attribute("unwhitelisted_key", format!("{}", key))
         .add_attribute("refund", format!("{}", refund_amt)))
@@ -216,14 +1642,14 @@ pub fn reclaim_deposit(
 /// Also ensures that the message/seed used for the VRF proof is the
 /// entropy that was last submitted.
 pub fn submit_entropy(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     data: SubmitEntropyMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.paused {
+    if cfg.pause_flags.submissions {
         return Err(ContractError::Paused {});
     }
     let proof = data.proof;
@@ -254,22 +1680,31 @@ pub fn submit_entropy(
                 Ok((id, req))
             })
             .collect::<Result<Vec<_>, ContractError>>()?
-    }.into_iter().take(512).collect::<Vec<_>>();
+    }
+    .into_iter()
+    .take(512)
+    .collect::<Vec<_>>();
 
     if state.last_entropy.unwrap_or_default() != proof.message_bytes && !cfg.test_mode {
         return Err(ContractError::InvalidMessage {});
     }
 
     let key = &proof.signer;
+    // Rewards accrue to the payout address of the key that earned them.
+    let mut rewarded_address = info.sender.clone();
     if !cfg.test_mode {
         check_key(&deps.as_ref(), &env, key, &cfg)?;
         let mut key_info = WHITELISTED_KEYS.load(deps.storage, key.as_bytes())?;
         if key_info.holder != info.sender {
             return Err(ContractError::Unauthorized {});
         }
+        let increment = deposit_terms(deps.storage, &cfg, &key_info.deposit_denom)?
+            .map(|terms| terms.refund_increment_amt)
+            .unwrap_or_default();
         key_info.refundable_amount =
-            (key_info.refundable_amount + cfg.refund_increment_amt).min(key_info.deposit_amount);
+            (key_info.refundable_amount + increment).min(key_info.deposit_amount);
         WHITELISTED_KEYS.save(deps.storage, key.as_bytes(), &key_info)?;
+        rewarded_address = key_info.payout_address().clone();
     }
 
     let entropy = if !cfg.test_mode {
@@ -282,14 +1717,90 @@ pub fn submit_entropy(
             .map_err(|_| ContractError::InvalidProof {})?
     };
 
+    // Demand is measured before the requests of this submission are fulfilled.
+    if let Some(adjustment) = &cfg.gas_price_adjustment {
+        let queue_depth = state.pending_requests;
+        state.belief_gas_price = adjustment.next_price(state.belief_gas_price, queue_depth);
+        let record = GasPriceRecord {
+            id: state.cur_gas_price_record_id,
+            height: env.block.height,
+            gas_price: state.belief_gas_price,
+            queue_depth,
+        };
+        GAS_PRICE_HISTORY.save(deps.storage, record.id, &record)?;
+        state.cur_gas_price_record_id += 1;
+    }
+
+    state.pending_requests = state.pending_requests.saturating_sub(requests.len() as u64);
     state.last_entropy = Some(entropy.to_vec());
     STATE.save(deps.storage, &state)?;
 
-    let payout: Uint128 = requests
-        .iter()
-        .map(|(_, req)| req.submitted_bounty_amount)
-        .sum();
-    let payout = payout * cfg.submitter_share;
+    let shares = cfg.fee_shares();
+    let mut payouts: Vec<(FeeRecipient, Vec<Coin>)> = vec![];
+    let mut split_events = vec![];
+    // What changes in the ledger of each denom that bounties were paid in.
+    let mut changes: BTreeMap<String, Ledger> = BTreeMap::new();
+    for (id, req) in requests.iter() {
+        let bounty = req.submitted_bounty_amount;
+        let change = changes.entry(req.denom.clone()).or_default();
+        let mut event = Event::new("fee_split")
+            .add_attribute("request_id", id.to_string())
+            .add_attribute("denom", &req.denom);
+        // Rounding dust stays with the protocol along with its own share.
+        let mut remainder = bounty;
+        for share in shares
+            .iter()
+            .filter(|s| s.recipient != FeeRecipient::Protocol)
+        {
+            // The referrer share of requests without a referrer is kept as revenue,
+            // and otherwise paid out to the referrer like a treasury share.
+            let recipient = match (&share.recipient, &req.referrer) {
+                (FeeRecipient::Referrer, None) => {
+                    event = event.add_attribute(share.recipient.as_str(), "0");
+                    continue;
+                }
+                (FeeRecipient::Referrer, Some(address)) => FeeRecipient::Treasury {
+                    address: address.clone(),
+                },
+                (recipient, _) => recipient.clone(),
+            };
+            let amount = bounty * share.share;
+            remainder -= amount;
+            event = event.add_attribute(share.recipient.as_str(), amount.to_string());
+            if recipient == FeeRecipient::Submitter {
+                change.rewards += amount;
+                continue;
+            }
+            match payouts.iter_mut().find(|(r, _)| r == &recipient) {
+                Some((_, coins)) => add_coin(coins, &req.denom, amount),
+                None => {
+                    let mut coins = vec![];
+                    add_coin(&mut coins, &req.denom, amount);
+                    payouts.push((recipient, coins));
+                }
+            }
+        }
+        change.bounties += bounty;
+        change.revenue += remainder;
+        split_events.push(event.add_attribute("protocol", remainder.to_string()));
+    }
+    let mut rewarded = vec![];
+    for (denom, change) in &changes {
+        let outgoing = change.bounties - change.revenue - change.rewards;
+        update_ledger_for_payout(deps.branch(), &env, denom, outgoing, |ledger| {
+            ledger.bounties = ledger.bounties.checked_sub(change.bounties)?;
+            ledger.revenue += change.revenue;
+            ledger.rewards += change.rewards;
+            Ok(())
+        })?;
+        // The submitter's share accrues until it is claimed.
+        REWARDS.update(
+            deps.storage,
+            (&rewarded_address, denom),
+            |rewards| -> StdResult<_> { Ok(rewards.unwrap_or_default() + change.rewards) },
+        )?;
+        add_coin(&mut rewarded, denom, change.rewards);
+    }
     let mut submsgs = vec![];
 
     // Flip one bit at a time and hash it to get the entropy for each request.
@@ -299,10 +1810,10 @@ pub fn submit_entropy(
 
         if !cfg.test_mode {
             cur_entropy[idx / 8] ^= 1 << (idx % 8);
-            hasher.update(&cur_entropy);
+            hasher.update(cur_entropy);
             cur_entropy = hasher.finalize().into();
         }
-        
+
         submsgs.push(SubMsg {
             id: SUBMSG_REPLY_ID,
             msg: EntropyCallbackMsg {
@@ -319,25 +1830,36 @@ pub fn submit_entropy(
     }
 
     let mut response = Response::new();
-    if !payout.is_zero() {
-        response = response.add_message(CosmosMsg::Bank(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: vec![Coin {
-                denom: cfg.native_denom,
-                amount: payout,
-            }],
-        }));
+    for (recipient, amount) in payouts {
+        if amount.is_empty() {
+            continue;
+        }
+        let to_address = match recipient {
+            FeeRecipient::Treasury { address } => address,
+            FeeRecipient::Burn => {
+                response = response.add_messages(burn_coins(amount)?);
+                continue;
+            }
+            FeeRecipient::Submitter | FeeRecipient::Protocol | FeeRecipient::Referrer => {
+                unreachable!("only shares sent to an address or burned are paid out")
+            }
+        };
+        response = response.add_messages(send_coins(&to_address, amount)?);
     }
 
+    let rewarded: Vec<_> = rewarded.iter().map(Coin::to_string).collect();
     Ok(response
         .add_submessages(submsgs)
+        .add_events(split_events)
         .add_attribute("action", "submit_entropy")
+        .add_attribute("rewards_accrued", rewarded.join(","))
         .add_attribute("entropy", encode_hex(&entropy)))
 }
 
 /// Allows any smart contract to request entropy from the beacon.
 /// Ensures that the caller has provided enough funds to pay both
 /// the requested callback gas and the protocol fee.
+/// Anything sent beyond the amount paid for the request is refunded.
 pub fn request_entropy(
     deps: DepsMut,
     env: Env,
@@ -345,47 +1867,621 @@ pub fn request_entropy(
     data: RequestEntropyMsg,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
-    if cfg.paused {
-        return Err(ContractError::Paused {});
+    if let Some(denom) = data.pay_from_credit.clone() {
+        if info.funds.iter().any(|c| !c.amount.is_zero()) {
+            return Err(ContractError::FundsWithCredit {});
+        }
+        let payment = Payment::Credit(denom);
+        return pay_for_request(deps, env, cfg, info.sender, payment, vec![], data);
+    }
+    // Requests sent without funds are paid from a fee grant, if one covers them.
+    if info.funds.iter().all(|c| c.amount.is_zero()) {
+        if let Some(grant) = find_fee_grant(deps.as_ref(), &env, &info.sender, &data)? {
+            let payment = Payment::Grant(grant);
+            return pay_for_request(deps, env, cfg, info.sender, payment, vec![], data);
+        }
     }
-    let mut state = STATE.load(deps.storage)?;
 
-    let received_funds_amt: Uint128 = info
+    // The fee is paid in the first denom sent that fees are accepted in.
+    let denom = info
         .funds
         .iter()
-        .filter(|c| c.denom == cfg.native_denom)
-        .map(|c| c.amount)
-        .sum();
+        .find(|c| {
+            !c.amount.is_zero()
+                && cw20_token(&c.denom).is_none()
+                && (c.denom == cfg.native_denom || FEE_DENOMS.has(deps.storage, &c.denom))
+        })
+        .map(|c| c.denom.clone())
+        .unwrap_or_else(|| cfg.native_denom.clone());
+    let (amount, foreign) = received_funds(&cfg, &info.funds, &denom)?;
+
+    let payment = Payment::Funds(Coin { denom, amount });
+    pay_for_request(deps, env, cfg, info.sender, payment, foreign, data)
+}
+
+/// Where the bounty of a request is paid from.
+enum Payment {
+    /// Funds that were received along with the request.
+    Funds(Coin),
+    /// The requester's prepaid credit in a denom.
+    Credit(String),
+    /// A sponsor's fee grant to the requester or callback address.
+    Grant(FeeGrant),
+}
+
+/// Finds a fee grant to the requester, or failing that to the callback
+/// address, that can cover the request in full. Grants to the callback
+/// address are only drawn on by the requesters that their sponsor allowed,
+/// as anyone can name any callback address.
+fn find_fee_grant(
+    deps: Deps,
+    env: &Env,
+    sender: &Addr,
+    data: &RequestEntropyMsg,
+) -> Result<Option<FeeGrant>, ContractError> {
+    for grantee in [sender, &data.callback_address] {
+        let grants = FEE_GRANTS
+            .sub_prefix(grantee)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (_, grant) in grants {
+            if &grant.grantee != sender && !grant.callback_requesters.contains(sender) {
+                continue;
+            }
+            let query = CalculateFeeQuery {
+                callback_gas_limit: data.callback_gas_limit,
+                denom: Some(grant.denom.clone()),
+            };
+            // The denom of the grant may no longer be accepted for fees.
+            let required_funds = match query::calculate_fee_query(deps, query) {
+                Ok(required_funds) => required_funds,
+                Err(_) => continue,
+            };
+            let fee = Uint128::from(required_funds.fee);
+            let needed = data.bounty.unwrap_or(fee).max(fee);
+            if grant.allowance(env.block.height) >= needed {
+                return Ok(Some(grant));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Creates a request paid for with funds that have already been received,
+/// or from the requester's credit.
+fn pay_for_request(
+    deps: DepsMut,
+    env: Env,
+    cfg: Config,
+    sender: Addr,
+    payment: Payment,
+    foreign: Vec<Coin>,
+    data: RequestEntropyMsg,
+) -> Result<Response, ContractError> {
+    if cfg.pause_flags.requests {
+        return Err(ContractError::Paused {});
+    }
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+    let mut state = STATE.load(deps.storage)?;
+    let (denom, available) = match &payment {
+        Payment::Funds(coin) => (coin.denom.clone(), coin.amount),
+        Payment::Credit(denom) => (
+            denom.clone(),
+            CREDITS
+                .may_load(deps.storage, (&sender, denom))?
+                .unwrap_or_default(),
+        ),
+        Payment::Grant(grant) => (grant.denom.clone(), grant.allowance(env.block.height)),
+    };
 
     let required_funds = query::calculate_fee_query(
         deps.as_ref(),
         CalculateFeeQuery {
             callback_gas_limit: data.callback_gas_limit,
+            denom: Some(denom.clone()),
         },
     )?;
 
-    if received_funds_amt < Uint128::from(required_funds.fee) {
-        return Err(ContractError::InsufficientFunds {});
+    // The sponsorship pool pays for the callback gas on top of the bounty.
+    let gas_cost = Uint128::from(data.callback_gas_limit) * required_funds.gas_price;
+    let subsidy = query::sponsored_gas(deps.as_ref(), &cfg, &denom, gas_cost)?;
+
+    let fee = Uint128::from(required_funds.fee);
+    if let Some(max_fee) = data.max_fee {
+        if fee > max_fee {
+            return Err(ContractError::FeeExceedsMax { fee, max_fee });
+        }
+    }
+    // Only the fee is charged unless a larger bounty is given explicitly.
+    let bounty = match data.bounty {
+        Some(bounty) if bounty < fee => {
+            return Err(ContractError::BountyBelowFee { fee });
+        }
+        Some(bounty) => bounty,
+        None => fee,
+    };
+    if available < bounty.max(fee) {
+        return Err(match payment {
+            Payment::Funds(_) | Payment::Grant(_) => ContractError::InsufficientFunds {},
+            Payment::Credit(_) => ContractError::InsufficientCredit { available },
+        });
+    }
+    // Whatever is not paid from credit or a grant is left there, rather than refunded.
+    let excess = match payment {
+        Payment::Funds(_) => available - bounty,
+        Payment::Credit(_) | Payment::Grant(_) => Uint128::zero(),
+    };
+    let refund_address = match data.refund_address {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => sender.clone(),
+    };
+    let referrer = data
+        .referrer
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    // Otherwise requesters could refer themselves to a share of their own fees.
+    if let Some(referrer) = &referrer {
+        if referrer == &sender || referrer == &data.callback_address {
+            return Err(ContractError::InvalidReferrer {});
+        }
     }
 
     let request_id = state.cur_request_id;
+    let fee_grant = match &payment {
+        Payment::Grant(grant) => Some(FeeGrantKey {
+            grantee: grant.grantee.clone(),
+            sponsor: grant.sponsor.clone(),
+        }),
+        Payment::Funds(_) | Payment::Credit(_) => None,
+    };
 
     let request = EntropyRequest {
         id: request_id,
         callback_gas_limit: data.callback_gas_limit,
         callback_address: data.callback_address,
         callback_msg: data.callback_msg,
-        submitter: info.sender,
+        submitter: sender.clone(),
         submitted_block_height: env.block.height,
-        submitted_bounty_amount: received_funds_amt,
+        submitted_bounty_amount: bounty + subsidy,
+        subsidy,
+        denom: denom.clone(),
+        referrer,
+        fee_grant,
     };
 
+    let from_credit = matches!(payment, Payment::Credit(_));
+    let mut sponsor = None;
+    match payment {
+        Payment::Funds(_) => {}
+        Payment::Credit(_) => {
+            let remaining = available - bounty;
+            if remaining.is_zero() {
+                CREDITS.remove(deps.storage, (&sender, &denom));
+            } else {
+                CREDITS.save(deps.storage, (&sender, &denom), &remaining)?;
+            }
+        }
+        Payment::Grant(mut grant) => {
+            grant.balance -= bounty;
+            grant.spent += bounty;
+            let key = (&grant.grantee, &grant.sponsor, denom.as_str());
+            FEE_GRANTS.save(deps.storage, key, &grant)?;
+            sponsor = Some(grant.sponsor);
+        }
+    }
+    update_ledger(deps.storage, &denom, |ledger| {
+        if from_credit {
+            ledger.credits = ledger.credits.checked_sub(bounty)?;
+        }
+        if sponsor.is_some() {
+            ledger.grants = ledger.grants.checked_sub(bounty)?;
+        }
+        ledger.sponsorship = ledger.sponsorship.checked_sub(subsidy)?;
+        ledger.bounties += bounty + subsidy;
+        Ok(())
+    })?;
+    if !subsidy.is_zero() {
+        let spend = SponsorshipSpend {
+            request_id: Uint128::from(request_id),
+            height: env.block.height,
+            denom: denom.clone(),
+            amount: subsidy,
+        };
+        SPONSORSHIP_SPENDS.save(deps.storage, request_id, &spend)?;
+    }
     ENTROPY_REQUESTS.save(deps.storage, request_id, &request)?;
 
     state.cur_request_id += 1;
+    state.pending_requests += 1;
     STATE.save(deps.storage, &state)?;
 
-    Ok(Response::new()
+    let mut response = Response::new().add_messages(refund_foreign(&refund_address, foreign));
+    if !excess.is_zero() {
+        let excess_coin = Coin {
+            denom,
+            amount: excess,
+        };
+        response = response.add_messages(send_coins(&refund_address, vec![excess_coin])?);
+    }
+    if let Some(sponsor) = sponsor {
+        response = response.add_attribute("sponsor", sponsor.as_str());
+    }
+
+    Ok(response
         .add_attribute("action", "request_entropy")
-        .add_attribute("request_id", format!("{}", request_id)))
+        .add_attribute("request_id", format!("{}", request_id))
+        .add_attribute("bounty", bounty.to_string())
+        .add_attribute("subsidy", subsidy.to_string())
+        .add_attribute("refund", excess.to_string()))
+}
+
+/// Handles cw20 tokens sent to the beacon, paying for a request or a key
+/// deposit in the token. The token must have been accepted through `SetFeeDenom`.
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let denom = cw20_denom(&info.sender);
+    if !FEE_DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::UnsupportedDenom { denom });
+    }
+    // Anything sent alongside the hook comes from the token contract, not the sender.
+    if let Some(coin) = info.funds.iter().find(|c| !c.amount.is_zero()) {
+        return Err(ContractError::UnsupportedDenom {
+            denom: coin.denom.clone(),
+        });
+    }
+
+    let sender = deps.api.addr_validate(&data.sender)?;
+    let received = Coin {
+        denom,
+        amount: data.amount,
+    };
+    match from_binary(&data.msg)? {
+        ReceiveMsg::RequestEntropy(msg) => {
+            if msg.pay_from_credit.is_some() {
+                return Err(ContractError::FundsWithCredit {});
+            }
+            let payment = Payment::Funds(received);
+            pay_for_request(deps, env, cfg, sender, payment, vec![], msg)
+        }
+        ReceiveMsg::WhitelistPublicKey(msg) => {
+            deposit_for_key(deps, env, cfg, sender, received, vec![], msg)
+        }
+        ReceiveMsg::DepositCredit(msg) => {
+            credit_account(deps, cfg, sender, vec![received], vec![], msg)
+        }
+        ReceiveMsg::FundSponsorship(_) => {
+            add_to_sponsorship(deps, cfg, sender, vec![received], vec![])
+        }
+        ReceiveMsg::GrantFee(msg) => {
+            add_fee_grant(deps, env, cfg, sender, vec![received], vec![], msg)
+        }
+    }
+}
+
+/// Adds the funds sent in denoms that fees are accepted in to the credit of an
+/// account, which requests can then be paid from.
+pub fn deposit_credit(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data: DepositCreditMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let (received, foreign) = split_funds(&cfg, &info.funds, |denom| {
+        denom == cfg.native_denom || FEE_DENOMS.has(deps.storage, denom)
+    })?;
+    credit_account(deps, cfg, info.sender, received, foreign, data)
+}
+
+/// Adds funds that have already been received to the credit of an account.
+fn credit_account(
+    deps: DepsMut,
+    cfg: Config,
+    sender: Addr,
+    received: Vec<Coin>,
+    foreign: Vec<Coin>,
+    data: DepositCreditMsg,
+) -> Result<Response, ContractError> {
+    if cfg.pause_flags.requests {
+        return Err(ContractError::Paused {});
+    }
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+    if received.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let account = match data.address {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => sender.clone(),
+    };
+
+    for coin in &received {
+        CREDITS.update(
+            deps.storage,
+            (&account, &coin.denom),
+            |credit| -> StdResult<_> { Ok(credit.unwrap_or_default() + coin.amount) },
+        )?;
+        update_ledger(deps.storage, &coin.denom, |ledger| {
+            ledger.credits += coin.amount;
+            Ok(())
+        })?;
+    }
+
+    let deposited: Vec<_> = received.iter().map(Coin::to_string).collect();
+    Ok(Response::new()
+        .add_messages(refund_foreign(&sender, foreign))
+        .add_attribute("action", "deposit_credit")
+        .add_attribute("account", account.as_str())
+        .add_attribute("amount", deposited.join(",")))
+}
+
+/// Withdraws credit that the sender has not spent on requests, either in full
+/// or in part, to the sender or another address.
+pub fn withdraw_credit(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: WithdrawCreditMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+
+    let recipient = match data.recipient {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => info.sender.clone(),
+    };
+
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let available = CREDITS
+        .may_load(deps.storage, (&info.sender, &denom))?
+        .unwrap_or_default();
+    let amount = data.amount.unwrap_or(available);
+    if amount > available {
+        return Err(ContractError::InsufficientCredit { available });
+    }
+    let remaining = available - amount;
+    if remaining.is_zero() {
+        CREDITS.remove(deps.storage, (&info.sender, &denom));
+    } else {
+        CREDITS.save(deps.storage, (&info.sender, &denom), &remaining)?;
+    }
+    update_ledger_for_payout(deps.branch(), &env, &denom, amount, |ledger| {
+        ledger.credits = ledger.credits.checked_sub(amount)?;
+        Ok(())
+    })?;
+
+    let response = Response::new()
+        .add_attribute("action", "withdraw_credit")
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("amount", amount.to_string());
+    if amount.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_messages(send_coins(&recipient, vec![Coin { denom, amount }])?))
+}
+
+/// Adds the funds sent in denoms that fees are accepted in to the sponsorship
+/// pool, which pays the callback gas of requests while callbacks are subsidized.
+/// Anyone can fund the pool, and funds cannot be withdrawn from it.
+pub fn fund_sponsorship(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    _data: FundSponsorshipMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let (received, foreign) = split_funds(&cfg, &info.funds, |denom| {
+        denom == cfg.native_denom || FEE_DENOMS.has(deps.storage, denom)
+    })?;
+    add_to_sponsorship(deps, cfg, info.sender, received, foreign)
+}
+
+/// Adds funds that have already been received to the sponsorship pool.
+fn add_to_sponsorship(
+    deps: DepsMut,
+    cfg: Config,
+    sender: Addr,
+    received: Vec<Coin>,
+    foreign: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+    if received.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    for coin in &received {
+        update_ledger(deps.storage, &coin.denom, |ledger| {
+            ledger.sponsorship += coin.amount;
+            Ok(())
+        })?;
+    }
+
+    let funded: Vec<_> = received.iter().map(Coin::to_string).collect();
+    Ok(Response::new()
+        .add_messages(refund_foreign(&sender, foreign))
+        .add_attribute("action", "fund_sponsorship")
+        .add_attribute("sponsor", sender.as_str())
+        .add_attribute("amount", funded.join(",")))
+}
+
+/// Withdraws what is left in the sponsorship pool once the beacon is being
+/// wound down, as no more requests can be subsidized from it.
+/// This is only allowed to be called by a holder of the `Treasurer` role.
+pub fn drain_sponsorship(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: DrainSponsorshipMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if !cfg.sunset {
+        return Err(ContractError::NotInSunset {});
+    }
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+    check_role(&deps.as_ref(), &cfg, Role::Treasurer, &info.sender)?;
+
+    let recipient = match data.recipient {
+        Some(addr) => deps.api.addr_validate(&addr)?,
+        None => info.sender.clone(),
+    };
+
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let amount = LEDGERS
+        .may_load(deps.storage, &denom)?
+        .unwrap_or_default()
+        .sponsorship;
+    update_ledger_for_payout(deps.branch(), &env, &denom, amount, |ledger| {
+        ledger.sponsorship = Uint128::zero();
+        Ok(())
+    })?;
+
+    let field = format!("ledgers.{}.sponsorship", denom);
+    let changes = vec![audit_change(field, &amount, &Uint128::zero())?];
+    record_audit(
+        deps.storage,
+        &env,
+        Some(info.sender),
+        "drain_sponsorship",
+        changes,
+    )?;
+
+    let response = Response::new()
+        .add_attribute("action", "drain_sponsorship")
+        .add_attribute("recipient", recipient.as_str())
+        .add_attribute("amount", amount.to_string());
+    if amount.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_messages(send_coins(&recipient, vec![Coin { denom, amount }])?))
+}
+
+/// Sets aside the funds sent in denoms that fees are accepted in to pay the
+/// fees of requests made by, or calling back to, the grantee.
+pub fn grant_fee(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: GrantFeeMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let (received, foreign) = split_funds(&cfg, &info.funds, |denom| {
+        denom == cfg.native_denom || FEE_DENOMS.has(deps.storage, denom)
+    })?;
+    add_fee_grant(deps, env, cfg, info.sender, received, foreign, data)
+}
+
+/// Adds funds that have already been received to the sender's grants to the
+/// grantee, one per denom, and sets the terms of each.
+fn add_fee_grant(
+    deps: DepsMut,
+    env: Env,
+    cfg: Config,
+    sender: Addr,
+    received: Vec<Coin>,
+    foreign: Vec<Coin>,
+    data: GrantFeeMsg,
+) -> Result<Response, ContractError> {
+    if cfg.pause_flags.requests {
+        return Err(ContractError::Paused {});
+    }
+    if cfg.sunset {
+        return Err(ContractError::Sunset {});
+    }
+    if received.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    if matches!(data.expires_at, Some(expiry) if expiry <= env.block.height) {
+        return Err(ContractError::InvalidFeeGrantExpiry {});
+    }
+    let grantee = deps.api.addr_validate(&data.grantee)?;
+    let callback_requesters = data
+        .callback_requesters
+        .unwrap_or_default()
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for coin in &received {
+        let key = (&grantee, &sender, coin.denom.as_str());
+        let mut grant = FEE_GRANTS
+            .may_load(deps.storage, key)?
+            .unwrap_or_else(|| FeeGrant {
+                sponsor: sender.clone(),
+                grantee: grantee.clone(),
+                callback_requesters: vec![],
+                denom: coin.denom.clone(),
+                balance: Uint128::zero(),
+                spend_limit: None,
+                per_request_limit: None,
+                expires_at: None,
+                spent: Uint128::zero(),
+            });
+        grant.balance += coin.amount;
+        grant.callback_requesters = callback_requesters.clone();
+        grant.spend_limit = data.spend_limit;
+        grant.per_request_limit = data.per_request_limit;
+        grant.expires_at = data.expires_at;
+        FEE_GRANTS.save(deps.storage, key, &grant)?;
+        update_ledger(deps.storage, &coin.denom, |ledger| {
+            ledger.grants += coin.amount;
+            Ok(())
+        })?;
+    }
+
+    let granted: Vec<_> = received.iter().map(Coin::to_string).collect();
+    Ok(Response::new()
+        .add_messages(refund_foreign(&sender, foreign))
+        .add_attribute("action", "grant_fee")
+        .add_attribute("sponsor", sender.as_str())
+        .add_attribute("grantee", grantee.as_str())
+        .add_attribute("amount", granted.join(",")))
+}
+
+/// Removes a fee grant made by the sender, and returns its unspent funds.
+pub fn revoke_fee_grant(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data: RevokeFeeGrantMsg,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.pause_flags.withdrawals {
+        return Err(ContractError::Paused {});
+    }
+
+    let grantee = deps.api.addr_validate(&data.grantee)?;
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let key = (&grantee, &info.sender, denom.as_str());
+    let grant = FEE_GRANTS
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::FeeGrantNotFound {})?;
+    FEE_GRANTS.remove(deps.storage, key);
+    let amount = grant.balance;
+    update_ledger_for_payout(deps.branch(), &env, &denom, amount, |ledger| {
+        ledger.grants = ledger.grants.checked_sub(amount)?;
+        Ok(())
+    })?;
+
+    let response = Response::new()
+        .add_attribute("action", "revoke_fee_grant")
+        .add_attribute("grantee", grantee.as_str())
+        .add_attribute("amount", amount.to_string());
+    if amount.is_zero() {
+        return Ok(response);
+    }
+    Ok(response.add_messages(send_coins(&info.sender, vec![Coin { denom, amount }])?))
 }
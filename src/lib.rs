@@ -1,9 +1,11 @@
 pub mod contract;
 pub mod execute;
+pub mod migrations;
 pub mod query;
 mod error;
 pub mod msg;
 pub mod state;
+pub mod sudo;
 mod utils;
 
 pub use crate::error::ContractError;
@@ -0,0 +1,241 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Decimal, DepsMut, Env, Order, StdResult, Uint128};
+use cw_storage_plus::{Item, Map};
+
+use crate::error::ContractError;
+use crate::state::{
+    Config, EntropyRequest, ForeignDenomPolicy, KeyInfo, Ledger, PauseFlags, State, CONFIG,
+    ENTROPY_REQUESTS, LEDGERS, STATE, WHITELISTED_KEYS,
+};
+
+/// An upgrade step, rewriting storage left by the previous version into the
+/// layout expected by the version the step is registered under.
+type MigrationStep = fn(DepsMut, &Env) -> StdResult<()>;
+
+/// Every version that changed the storage layout, in ascending order.
+const MIGRATION_STEPS: &[(&str, MigrationStep)] = &[("2.2.0", migrate_v2_2_0)];
+
+/// The oldest major version that can be migrated from.
+const MIN_MAJOR_VERSION: u64 = 2;
+
+/// A `major.minor.patch` version, ignoring any pre-release or build suffix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(u64, u64, u64);
+
+impl Version {
+    pub fn parse(version: &str) -> Result<Self, ContractError> {
+        let invalid = || ContractError::UnsupportedMigrationVersion {
+            version: version.to_string(),
+        };
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        let parts = core
+            .split('.')
+            .map(|p| p.parse::<u64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts[..] {
+            [major, minor, patch] => Ok(Version(major, minor, patch)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Runs every upgrade step newer than `from` and no newer than `to`, in order.
+/// Returns the versions whose steps were applied.
+pub fn run_migrations(
+    mut deps: DepsMut,
+    env: &Env,
+    from: &str,
+    to: &str,
+) -> Result<Vec<String>, ContractError> {
+    let from_version = Version::parse(from)?;
+    let to_version = Version::parse(to)?;
+
+    if from_version.0 < MIN_MAJOR_VERSION {
+        return Err(ContractError::UnsupportedMigrationVersion {
+            version: from.to_string(),
+        });
+    }
+    if from_version > to_version {
+        return Err(ContractError::MigrationDowngrade {
+            from: from.to_string(),
+            to: to.to_string(),
+        });
+    }
+
+    let mut applied = vec![];
+    for (version, step) in MIGRATION_STEPS {
+        let step_version = Version::parse(version)?;
+        if step_version > from_version && step_version <= to_version {
+            step(deps.branch(), env)?;
+            applied.push(version.to_string());
+        }
+    }
+    Ok(applied)
+}
+
+/// Storage layout written by every release before 2.2.0.
+pub(crate) mod v2_1 {
+    use super::*;
+
+    #[cw_serde]
+    pub struct State {
+        pub last_entropy: Option<Vec<u8>>,
+        pub belief_gas_price: Decimal,
+        pub cur_request_id: u128,
+    }
+
+    #[cw_serde]
+    pub struct Config {
+        pub owner: Addr,
+        pub whitelist_deposit_amt: Uint128,
+        pub refund_increment_amt: Uint128,
+        pub key_activation_delay: u64,
+        pub protocol_fee: u64,
+        pub submitter_share: Decimal,
+        pub native_denom: String,
+        pub paused: bool,
+        pub permissioned: bool,
+        pub test_mode: bool,
+        pub subsidize_callbacks: bool,
+    }
+
+    #[cw_serde]
+    pub struct KeyInfo {
+        pub holder: Addr,
+        pub deposit_amount: Uint128,
+        pub refundable_amount: Uint128,
+        pub creation_height: u64,
+    }
+
+    #[cw_serde]
+    pub struct EntropyRequest {
+        pub id: u128,
+        pub callback_gas_limit: u64,
+        pub callback_address: Addr,
+        pub callback_msg: Binary,
+        pub submitter: Addr,
+        pub submitted_block_height: u64,
+        pub submitted_bounty_amount: Uint128,
+    }
+
+    pub const STATE: Item<State> = Item::new("state");
+    pub const CONFIG: Item<Config> = Item::new("config");
+    pub const WHITELISTED_KEYS: Map<&[u8], KeyInfo> = Map::new("whitelisted_keys");
+    pub const ENTROPY_REQUESTS: Map<u128, EntropyRequest> = Map::new("entropy_requests");
+}
+
+/// Makes the owner optional, splits the pause switch into per-interaction
+/// flags, and adds the config timelock, its change counter, the sunset flag,
+/// the successor and predecessor beacons, the fee split and the policy for
+/// foreign denoms.
+///
+/// Also starts tracking the funds owed in the native denom. Protocol revenue
+/// was not tracked before, so whatever part of the balance is not owed to key
+/// holders or requesters is taken as revenue. Pending requests are marked as
+/// paid in the native denom, the only one accepted before, as are the deposits
+/// of whitelisted keys.
+///
+/// Callbacks are now only subsidized from the sponsorship pool. Beacons that
+/// subsidized callbacks paid for them out of that same surplus, so for them it
+/// seeds the pool instead of being taken as revenue, and they keep subsidizing
+/// callbacks after the upgrade.
+fn migrate_v2_2_0(deps: DepsMut, env: &Env) -> StdResult<()> {
+    let storage = deps.storage;
+    let old = v2_1::STATE.load(storage)?;
+    let state = State {
+        last_entropy: old.last_entropy,
+        belief_gas_price: old.belief_gas_price,
+        cur_request_id: old.cur_request_id,
+        cur_config_change_id: 0,
+        pending_requests: v2_1::ENTROPY_REQUESTS
+            .keys(storage, None, None, Order::Ascending)
+            .count() as u64,
+        cur_gas_price_record_id: 0,
+    };
+    STATE.save(storage, &state)?;
+
+    let old = v2_1::CONFIG.load(storage)?;
+    let cfg = Config {
+        owner: Some(old.owner),
+        whitelist_deposit_amt: old.whitelist_deposit_amt,
+        refund_increment_amt: old.refund_increment_amt,
+        key_activation_delay: old.key_activation_delay,
+        protocol_fee: old.protocol_fee,
+        submitter_share: old.submitter_share,
+        native_denom: old.native_denom,
+        pause_flags: PauseFlags::all(old.paused),
+        permissioned: old.permissioned,
+        test_mode: old.test_mode,
+        subsidize_callbacks: old.subsidize_callbacks,
+        config_timelock: 0,
+        sunset: false,
+        successor: None,
+        predecessor: None,
+        fee_split: None,
+        foreign_denoms: ForeignDenomPolicy::Reject,
+        gas_price_adjustment: None,
+    };
+    CONFIG.save(storage, &cfg)?;
+
+    let keys = v2_1::WHITELISTED_KEYS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut deposits = Uint128::zero();
+    for (key, old) in keys {
+        deposits += old.deposit_amount;
+        let info = KeyInfo {
+            holder: old.holder,
+            deposit_amount: old.deposit_amount,
+            refundable_amount: old.refundable_amount,
+            creation_height: old.creation_height,
+            payout_address: None,
+            deposit_denom: cfg.native_denom.clone(),
+        };
+        WHITELISTED_KEYS.save(storage, &key, &info)?;
+    }
+
+    let requests = v2_1::ENTROPY_REQUESTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut bounties = Uint128::zero();
+    for (id, old) in requests {
+        bounties += old.submitted_bounty_amount;
+        let request = EntropyRequest {
+            id: old.id,
+            callback_gas_limit: old.callback_gas_limit,
+            callback_address: old.callback_address,
+            callback_msg: old.callback_msg,
+            submitter: old.submitter,
+            submitted_block_height: old.submitted_block_height,
+            submitted_bounty_amount: old.submitted_bounty_amount,
+            subsidy: Uint128::zero(),
+            denom: cfg.native_denom.clone(),
+            referrer: None,
+            fee_grant: None,
+        };
+        ENTROPY_REQUESTS.save(storage, id, &request)?;
+    }
+
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, &cfg.native_denom)?
+        .amount;
+    let surplus = balance.saturating_sub(deposits + bounties);
+    let (revenue, sponsorship) = if cfg.subsidize_callbacks {
+        (Uint128::zero(), surplus)
+    } else {
+        (surplus, Uint128::zero())
+    };
+    let ledger = Ledger {
+        deposits,
+        bounties,
+        revenue,
+        rewards: Uint128::zero(),
+        credits: Uint128::zero(),
+        sponsorship,
+        grants: Uint128::zero(),
+    };
+    LEDGERS.save(storage, &cfg.native_denom, &ledger)?;
+
+    Ok(())
+}
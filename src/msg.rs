@@ -1,6 +1,776 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use ecvrf_rs::PublicKey;
+use entropy_beacon_cosmos::{
+    beacon::{self, UpdateConfigMsg},
+    provide::{
+        ActiveRequestsQuery, AdminReturnDepositMsg, BeaconConfigQuery, KeyStatusQuery,
+        LastEntropyQuery, ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg,
+    },
+};
+
+use crate::state::{
+    AuditEntry, Config, DenomPricing, EntropyRequest, FeeGrant, FeeGrantKey, FeeShare,
+    ForeignDenomPolicy, GasPriceAdjustment, GasPriceRecord, KeyInfo, Ledger, PendingConfigChange,
+    Role, SponsorshipSpend, State,
+};
 
 pub const SUBMSG_REPLY_ID: u64 = 1;
 
+/// The version of the format produced by `ExportState` and accepted by `ImportState`.
+pub const STATE_EXPORT_VERSION: u32 = 4;
+
+/// Execute messages understood by the beacon. The first group mirrors
+/// `entropy_beacon_cosmos::msg::ExecuteMsg` so existing integrations keep
+/// working unchanged; the rest are administrative extensions.
+#[cw_serde]
+pub enum ExecuteMsg {
+    WhitelistPublicKey(WhitelistPublicKeyMsg),
+    ReclaimDeposit(ReclaimDepositMsg),
+    SubmitEntropy(SubmitEntropyMsg),
+    RequestEntropy(RequestEntropyMsg),
+    UpdateConfig(UpdateConfigMsg),
+    AdminReturnDeposit(AdminReturnDepositMsg),
+    UpdateGasPrice(Decimal),
+
+    ProposeOwner(ProposeOwnerMsg),
+    AcceptOwnership(AcceptOwnershipMsg),
+    CancelOwnershipTransfer(CancelOwnershipTransferMsg),
+    RenounceOwnership(RenounceOwnershipMsg),
+    GrantRole(GrantRoleMsg),
+    RevokeRole(RevokeRoleMsg),
+    SetPaused(SetPausedMsg),
+    UpdateConfigTimelock(UpdateConfigTimelockMsg),
+    ApplyConfigChange(ApplyConfigChangeMsg),
+    CancelConfigChange(CancelConfigChangeMsg),
+    ExitTestMode(ExitTestModeMsg),
+    StartSunset(StartSunsetMsg),
+    ProcessSunset(ProcessSunsetMsg),
+    SetSuccessor(SetSuccessorMsg),
+    SetPredecessor(SetPredecessorMsg),
+    ForwardRequests(ForwardRequestsMsg),
+    ReceiveForwardedRequests(ReceiveForwardedRequestsMsg),
+    ImportState(ImportStateMsg),
+    WithdrawRevenue(WithdrawRevenueMsg),
+    SetFeeSplit(SetFeeSplitMsg),
+    ClaimRewards(ClaimRewardsMsg),
+    SetPayoutAddress(SetPayoutAddressMsg),
+    SetForeignDenomPolicy(SetForeignDenomPolicyMsg),
+    SetFeeDenom(SetFeeDenomMsg),
+    Receive(Cw20ReceiveMsg),
+    DepositCredit(DepositCreditMsg),
+    WithdrawCredit(WithdrawCreditMsg),
+    FundSponsorship(FundSponsorshipMsg),
+    DrainSponsorship(DrainSponsorshipMsg),
+    GrantFee(GrantFeeMsg),
+    RevokeFeeGrant(RevokeFeeGrantMsg),
+    SetGasPriceAdjustment(SetGasPriceAdjustmentMsg),
+}
+
+/// Messages that can be sent along with cw20 tokens, paying for them in the
+/// token. The token must have been accepted through `SetFeeDenom`.
+#[cw_serde]
+pub enum ReceiveMsg {
+    RequestEntropy(RequestEntropyMsg),
+    WhitelistPublicKey(WhitelistPublicKeyMsg),
+    DepositCredit(DepositCreditMsg),
+    FundSponsorship(FundSponsorshipMsg),
+    GrantFee(GrantFeeMsg),
+}
+
+/// Requests entropy from the beacon. Accepts everything that
+/// `entropy_beacon_cosmos::beacon::RequestEntropyMsg` does, along with optional
+/// limits on what the request costs.
+#[cw_serde]
+pub struct RequestEntropyMsg {
+    ///The amount of gas that has been provisioned for the callback message.
+    pub callback_gas_limit: u64,
+    ///The address to send the callback message to.
+    pub callback_address: Addr,
+    ///Arbitrary data to be included in the callback message.
+    pub callback_msg: Binary,
+    ///The highest fee that the requester is willing to pay. The request is
+    ///rejected if the current fee is higher.
+    pub max_fee: Option<Uint128>,
+    ///The exact amount to pay for the request, which must cover the current
+    ///fee. Without it, only the current fee is charged.
+    pub bounty: Option<Uint128>,
+    ///Where anything sent beyond the amount paid is refunded, instead of to
+    ///the sender.
+    pub refund_address: Option<String>,
+    ///Pays from the sender's credit in this denom instead of with funds sent
+    ///along. Only the current fee is charged if no explicit `bounty` is given.
+    pub pay_from_credit: Option<String>,
+    ///The address that receives the referrer share of the bounty, other than
+    ///the requester or the callback address.
+    pub referrer: Option<String>,
+}
+
+impl From<beacon::RequestEntropyMsg> for RequestEntropyMsg {
+    fn from(msg: beacon::RequestEntropyMsg) -> Self {
+        RequestEntropyMsg {
+            callback_gas_limit: msg.callback_gas_limit,
+            callback_address: msg.callback_address,
+            callback_msg: msg.callback_msg,
+            max_fee: None,
+            bounty: None,
+            refund_address: None,
+            pay_from_credit: None,
+            referrer: None,
+        }
+    }
+}
+
+/// Calculates the fee for a request. Accepts everything that
+/// `entropy_beacon_cosmos::beacon::CalculateFeeQuery` does, along with the
+/// denom to calculate the fee in, which defaults to the native denom.
+#[cw_serde]
+pub struct CalculateFeeQuery {
+    pub callback_gas_limit: u64,
+    pub denom: Option<String>,
+}
+
+impl From<beacon::CalculateFeeQuery> for CalculateFeeQuery {
+    fn from(query: beacon::CalculateFeeQuery) -> Self {
+        CalculateFeeQuery {
+            callback_gas_limit: query.callback_gas_limit,
+            denom: None,
+        }
+    }
+}
+
+/// Query messages understood by the beacon. The first group mirrors
+/// `entropy_beacon_cosmos::msg::QueryMsg`.
+#[cw_serde]
+pub enum QueryMsg {
+    KeyStatus(KeyStatusQuery),
+    LastEntropy(LastEntropyQuery),
+    ActiveRequests(ActiveRequestsQuery),
+    BeaconConfig(BeaconConfigQuery),
+    CalculateFee(CalculateFeeQuery),
+
+    Ownership(OwnershipQuery),
+    Roles(RolesQuery),
+    RoleMembers(RoleMembersQuery),
+    PendingConfigChanges(PendingConfigChangesQuery),
+    PauseFlags(PauseFlagsQuery),
+    AuditLog(AuditLogQuery),
+    ValidateConfigUpdate(UpdateConfigMsg),
+    SunsetStatus(SunsetStatusQuery),
+    ExportState(ExportStateQuery),
+    ProtocolRevenue(ProtocolRevenueQuery),
+    Solvency(SolvencyQuery),
+    FeeSplit(FeeSplitQuery),
+    UnclaimedRewards(UnclaimedRewardsQuery),
+    FeeDenoms(FeeDenomsQuery),
+    Credit(CreditQuery),
+    SponsorshipPool(SponsorshipPoolQuery),
+    SponsorshipSpends(SponsorshipSpendsQuery),
+    FeeGrant(FeeGrantQuery),
+    FeeGrants(FeeGrantsQuery),
+    GasPriceHistory(GasPriceHistoryQuery),
+}
+
+/// Proposes a new owner for the beacon. The transfer only takes effect once
+/// the proposed address accepts it. Proposing again replaces the pending owner.
+#[cw_serde]
+pub struct ProposeOwnerMsg {
+    pub new_owner: String,
+}
+
+/// Accepts a pending ownership transfer. Must be sent by the proposed owner.
+#[cw_serde]
+pub struct AcceptOwnershipMsg {}
+
+/// Cancels a pending ownership transfer. Must be sent by the current owner.
+#[cw_serde]
+pub struct CancelOwnershipTransferMsg {}
+
+/// Permanently gives up ownership of the beacon. After this, no owner-only
+/// operation can ever be executed again.
+#[cw_serde]
+pub struct RenounceOwnershipMsg {}
+
+#[cw_serde]
+pub struct OwnershipQuery {}
+
+#[cw_serde]
+pub struct OwnershipResponse {
+    ///The current owner, or `None` if ownership has been renounced.
+    pub owner: Option<Addr>,
+    ///The address that has been proposed as the next owner, if any.
+    pub pending_owner: Option<Addr>,
+}
+
+/// Grants a role to an address. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct GrantRoleMsg {
+    pub role: Role,
+    pub address: String,
+}
+
+/// Revokes a role from an address. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct RevokeRoleMsg {
+    pub role: Role,
+    pub address: String,
+}
+
+/// Pauses or unpauses parts of the beacon. Flags that are not set are left
+/// unchanged. Requires the `Pauser` role.
+#[cw_serde]
+pub struct SetPausedMsg {
+    pub requests: Option<bool>,
+    pub submissions: Option<bool>,
+    pub whitelisting: Option<bool>,
+    pub withdrawals: Option<bool>,
+}
+
+/// Lists the roles held by an address, including those implied by ownership.
+#[cw_serde]
+pub struct RolesQuery {
+    pub address: String,
+}
+
+#[cw_serde]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+/// Lists the addresses that have explicitly been granted a role.
+#[cw_serde]
+pub struct RoleMembersQuery {
+    pub role: Role,
+    pub start_after: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct RoleMembersResponse {
+    pub members: Vec<Addr>,
+}
+
+/// Sets the number of blocks that config changes must wait before they can
+/// be applied. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct UpdateConfigTimelockMsg {
+    pub blocks: u64,
+}
+
+/// Applies a scheduled config change once its timelock has passed.
+#[cw_serde]
+pub struct ApplyConfigChangeMsg {
+    pub id: u64,
+}
+
+/// Cancels a scheduled config change. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct CancelConfigChangeMsg {
+    pub id: u64,
+}
+
+/// Permanently switches the beacon out of test mode. This is only allowed to
+/// be called by the owner, and only once every request made under test mode
+/// has been fulfilled.
+#[cw_serde]
+pub struct ExitTestModeMsg {}
+
+/// Permanently starts winding down the beacon, refusing new requests and
+/// whitelisting. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct StartSunsetMsg {}
+
+/// Refunds up to `limit` pending requests and key deposits while the beacon
+/// is being wound down. Can be called by anyone.
+#[cw_serde]
+pub struct ProcessSunsetMsg {
+    pub limit: Option<u32>,
+}
+
+/// Designates the beacon that pending requests are handed off to, or clears
+/// it. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetSuccessorMsg {
+    pub address: Option<String>,
+}
+
+/// Designates the beacon that is allowed to hand off its pending requests to
+/// this one, or clears it. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetPredecessorMsg {
+    pub address: Option<String>,
+}
+
+/// Hands off up to `limit` pending requests, oldest first, to the successor
+/// beacon along with their bounties. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct ForwardRequestsMsg {
+    pub limit: Option<u32>,
+}
+
+/// Re-creates requests handed off by the predecessor beacon. The bounties of
+/// every request must be sent along as funds.
+#[cw_serde]
+pub struct ReceiveForwardedRequestsMsg {
+    pub requests: Vec<ForwardedRequest>,
+}
+
+/// A pending request, as handed off between beacons.
+#[cw_serde]
+pub struct ForwardedRequest {
+    ///The address that originally made the request.
+    pub requester: Addr,
+    ///How much gas the requester has provisioned for their callback transaction.
+    pub callback_gas_limit: u64,
+    ///The address to send the callback message to.
+    pub callback_address: Addr,
+    ///A custom callback message that was included by the requester.
+    pub callback_msg: Binary,
+    ///The bounty that was paid for the request.
+    pub bounty: Uint128,
+    ///The denom that the bounty was paid in.
+    pub denom: String,
+    ///The address that referred the request, if any.
+    pub referrer: Option<Addr>,
+    ///The fee grant that paid the bounty, if any, so that it is refunded to
+    ///the grant or its sponsor rather than the requester.
+    pub fee_grant: Option<FeeGrantKey>,
+}
+
+/// Lists the config changes that have been scheduled but not applied yet.
+#[cw_serde]
+pub struct PendingConfigChangesQuery {
+    pub start_after: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct PendingConfigChangesResponse {
+    pub changes: Vec<PendingConfigChange>,
+}
+
+/// Returns which parts of the beacon are currently paused.
+#[cw_serde]
+pub struct PauseFlagsQuery {}
+
+/// Lists the recorded privileged actions, oldest first.
+#[cw_serde]
+pub struct AuditLogQuery {
+    pub start_after: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+/// The result of dry-running an `UpdateConfigMsg` against the current config.
+#[cw_serde]
+pub struct ValidateConfigUpdateResponse {
+    ///Whether or not the update would be accepted.
+    pub valid: bool,
+    ///A description of every invariant that the update would violate.
+    pub errors: Vec<String>,
+}
+
+/// Withdraws accrued protocol revenue in a denom, the native denom unless
+/// `denom` is set. Withdraws all of it if `amount` is not set, and sends it to
+/// the sender unless `recipient` is set. Requires the `Treasurer` role.
+#[cw_serde]
+pub struct WithdrawRevenueMsg {
+    pub amount: Option<Uint128>,
+    pub recipient: Option<String>,
+    pub denom: Option<String>,
+}
+
+/// Returns the protocol revenue that has accrued and not been withdrawn yet.
+#[cw_serde]
+pub struct ProtocolRevenueQuery {}
+
+#[cw_serde]
+pub struct ProtocolRevenueResponse {
+    ///The withdrawable revenue, per denom.
+    pub revenue: Vec<Coin>,
+}
+
+/// Sets how the bounty of each fulfilled request is split, or restores the
+/// default split by `submitter_share` when `shares` is `None`. The shares must
+/// sum to 100%. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetFeeSplitMsg {
+    pub shares: Option<Vec<FeeShare>>,
+}
+
+/// Pays out rewards accrued by `address`, or by the sender if it is not set, in
+/// a denom, the native denom unless `denom` is set. Claims everything when
+/// `amount` is not set. Rewards are always paid to the address that accrued
+/// them.
+#[cw_serde]
+pub struct ClaimRewardsMsg {
+    pub amount: Option<Uint128>,
+    pub address: Option<String>,
+    pub denom: Option<String>,
+}
+
+/// Sets where the rewards and deposit refunds of a key are sent, or sends them
+/// to the holder again when `address` is `None`. This is only allowed to be
+/// called by the holder of the key.
+#[cw_serde]
+pub struct SetPayoutAddressMsg {
+    pub public_key: PublicKey,
+    pub address: Option<String>,
+}
+
+/// Adds every coin sent in an accepted denom to the credit of an address, or
+/// of the sender if no `address` is given. Requests can then be paid from it.
+#[cw_serde]
+pub struct DepositCreditMsg {
+    pub address: Option<String>,
+}
+
+/// Withdraws the sender's credit in the native denom, or in another denom if
+/// `denom` is set. Withdraws everything when `amount` is not set, and pays the
+/// sender unless a `recipient` is given.
+#[cw_serde]
+pub struct WithdrawCreditMsg {
+    pub amount: Option<Uint128>,
+    pub recipient: Option<String>,
+    pub denom: Option<String>,
+}
+
+/// Returns the credit that an address has left to pay for requests with.
+#[cw_serde]
+pub struct CreditQuery {
+    pub address: String,
+}
+
+#[cw_serde]
+pub struct CreditResponse {
+    pub credits: Vec<Coin>,
+}
+
+/// Adds every coin sent in an accepted denom to the sponsorship pool. While
+/// callbacks are subsidized, the pool pays for the callback gas of each request
+/// in the denom it is paid in, for as long as it can cover it.
+#[cw_serde]
+pub struct FundSponsorshipMsg {}
+
+/// Withdraws what is left in the sponsorship pool in a denom, the native denom
+/// unless `denom` is set, and sends it to the sender unless `recipient` is set.
+/// Only allowed while the beacon is being wound down, and requires the
+/// `Treasurer` role.
+#[cw_serde]
+pub struct DrainSponsorshipMsg {
+    pub recipient: Option<String>,
+    pub denom: Option<String>,
+}
+
+/// Returns what is left in the sponsorship pool in the native denom, or in
+/// another denom if `denom` is set.
+#[cw_serde]
+pub struct SponsorshipPoolQuery {
+    pub denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct SponsorshipPoolResponse {
+    pub denom: String,
+    pub balance: Uint128,
+    ///Whether callbacks are subsidized at all.
+    pub subsidize_callbacks: bool,
+}
+
+/// Lists the callback gas paid for by the sponsorship pool, oldest request first.
+#[cw_serde]
+pub struct SponsorshipSpendsQuery {
+    pub start_after: Option<Uint128>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct SponsorshipSpendsResponse {
+    pub spends: Vec<SponsorshipSpend>,
+}
+
+/// Sets aside every coin sent in an accepted denom to pay the fees of requests
+/// made by `grantee`, or calling back to it from one of `callback_requesters`.
+/// Granting again in the same denom adds to the funds and replaces the terms
+/// of the grant.
+#[cw_serde]
+pub struct GrantFeeMsg {
+    pub grantee: String,
+    ///Requesters other than the grantee whose requests calling back to the
+    ///grantee are also paid for.
+    pub callback_requesters: Option<Vec<String>>,
+    ///The most that can be spent over the lifetime of the grant.
+    pub spend_limit: Option<Uint128>,
+    ///The most that can be spent on a single request.
+    pub per_request_limit: Option<Uint128>,
+    ///The block from which the grant can no longer be used.
+    pub expires_at: Option<u64>,
+}
+
+/// Removes a fee grant made by the sender in the native denom, or in another
+/// denom if `denom` is set, and returns the unspent funds to the sender.
+#[cw_serde]
+pub struct RevokeFeeGrantMsg {
+    pub grantee: String,
+    pub denom: Option<String>,
+}
+
+/// Returns the grant that a sponsor has made to an address in the native
+/// denom, or in another denom if `denom` is set.
+#[cw_serde]
+pub struct FeeGrantQuery {
+    pub sponsor: String,
+    pub grantee: String,
+    pub denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct FeeGrantResponse {
+    pub grant: Option<FeeGrantInfo>,
+}
+
+/// Returns every grant made to an address.
+#[cw_serde]
+pub struct FeeGrantsQuery {
+    pub grantee: String,
+}
+
+#[cw_serde]
+pub struct FeeGrantsResponse {
+    pub grants: Vec<FeeGrantInfo>,
+}
+
+#[cw_serde]
+pub struct FeeGrantInfo {
+    pub grant: FeeGrant,
+    ///The most that a single request can currently be paid from the grant.
+    pub allowance: Uint128,
+}
+
+/// Returns the rewards that an address has accrued and not claimed yet.
+#[cw_serde]
+pub struct UnclaimedRewardsQuery {
+    pub address: String,
+}
+
+#[cw_serde]
+pub struct UnclaimedRewardsResponse {
+    pub rewards: Vec<Coin>,
+}
+
+/// Sets whether coins sent in denoms other than the native denom are rejected
+/// or refunded. This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetForeignDenomPolicyMsg {
+    pub policy: ForeignDenomPolicy,
+}
+
+/// Accepts fees in a denom other than the native denom at the given price,
+/// updates its price, or stops accepting it when `pricing` is `None`.
+/// Cw20 tokens are accepted under the denom `cw20:<token address>`.
+/// This is only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetFeeDenomMsg {
+    pub denom: String,
+    pub pricing: Option<DenomPricing>,
+}
+
+/// Returns every denom other than the native denom that fees can be paid in.
+#[cw_serde]
+pub struct FeeDenomsQuery {}
+
+#[cw_serde]
+pub struct FeeDenomsResponse {
+    pub denoms: Vec<FeeDenom>,
+}
+
+#[cw_serde]
+pub struct FeeDenom {
+    pub denom: String,
+    pub pricing: DenomPricing,
+}
+
+/// Returns the split that is applied to each fulfilled request.
+#[cw_serde]
+pub struct FeeSplitQuery {}
+
+#[cw_serde]
+pub struct FeeSplitResponse {
+    pub shares: Vec<FeeShare>,
+}
+
+/// Reconciles the funds owed in a denom, the native denom unless `denom` is
+/// set, against the beacon's balance.
+#[cw_serde]
+pub struct SolvencyQuery {
+    pub denom: Option<String>,
+}
+
+#[cw_serde]
+pub struct SolvencyResponse {
+    ///The denom that every amount below is in.
+    pub denom: String,
+    ///The beacon's bank balance.
+    pub balance: Uint128,
+    ///Deposits made to whitelist keys.
+    pub deposits: Uint128,
+    ///Bounties of pending requests.
+    pub bounties: Uint128,
+    ///Protocol revenue that has not been withdrawn yet.
+    pub revenue: Uint128,
+    ///Submitter rewards that have not been claimed yet.
+    pub rewards: Uint128,
+    ///Requester credit that has not been spent yet.
+    pub credits: Uint128,
+    ///Sponsorship funds that have not been spent yet.
+    pub sponsorship: Uint128,
+    ///Fee grant funds that have not been spent or returned yet.
+    pub grants: Uint128,
+    ///The sum of every amount owed.
+    pub liabilities: Uint128,
+    ///How far the balance falls short of the liabilities.
+    pub shortfall: Uint128,
+    ///Whether or not the balance covers every liability.
+    pub solvent: bool,
+}
+
+/// Exports a page of the beacon's state. Every page carries the full `State`,
+/// `Config`, ledgers, roles, pending owner and pending config changes, along
+/// with up to `limit` entries of each other collection following the given
+/// cursors.
+#[cw_serde]
+pub struct ExportStateQuery {
+    pub start_after_key: Option<PublicKey>,
+    pub start_after_request: Option<Uint128>,
+    ///The `(address, denom)` of the last exported reward.
+    pub start_after_reward: Option<(String, String)>,
+    ///The `(address, denom)` of the last exported credit.
+    pub start_after_credit: Option<(String, String)>,
+    ///The `(grantee, sponsor, denom)` of the last exported fee grant.
+    pub start_after_grant: Option<(String, String, String)>,
+    ///The request id of the last exported sponsorship spend.
+    pub start_after_spend: Option<Uint128>,
+    ///The id of the last exported gas price record.
+    pub start_after_gas_price: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+/// A page of exported beacon state.
+#[cw_serde]
+pub struct StateExport {
+    ///The version of the export format, see `STATE_EXPORT_VERSION`.
+    pub version: u32,
+    pub state: State,
+    pub config: Config,
+    pub keys: Vec<ExportedKey>,
+    pub requests: Vec<EntropyRequest>,
+    pub fee_denoms: Vec<FeeDenom>,
+    pub ledgers: Vec<ExportedLedger>,
+    pub rewards: Vec<ExportedReward>,
+    pub credits: Vec<ExportedCredit>,
+    pub grants: Vec<FeeGrant>,
+    pub pending_owner: Option<Addr>,
+    pub roles: Vec<ExportedRole>,
+    pub pending_config_changes: Vec<PendingConfigChange>,
+    pub sponsorship_spends: Vec<SponsorshipSpend>,
+    pub gas_price_history: Vec<GasPriceRecord>,
+}
+
+#[cw_serde]
+pub struct ExportedKey {
+    pub public_key: PublicKey,
+    pub info: KeyInfo,
+}
+
+#[cw_serde]
+pub struct ExportedLedger {
+    pub denom: String,
+    pub ledger: Ledger,
+}
+
+#[cw_serde]
+pub struct ExportedReward {
+    pub address: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ExportedRole {
+    pub role: Role,
+    pub address: Addr,
+}
+
+#[cw_serde]
+pub struct ExportedCredit {
+    pub address: Addr,
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+/// Imports a page of exported state into a freshly instantiated beacon.
+/// Pages can be imported in any order, and `finalize` closes the import for
+/// good. This is only allowed to be called by the owner, and only until the
+/// import is finalized, so the owner should finalize it before the beacon is
+/// put to use, as an import overwrites the `State`.
+#[cw_serde]
+pub struct ImportStateMsg {
+    pub export: Box<StateExport>,
+    pub finalize: bool,
+}
+
+/// Returns whether the beacon is being wound down, and how far along it is.
+#[cw_serde]
+pub struct SunsetStatusQuery {}
+
+#[cw_serde]
+pub struct SunsetStatusResponse {
+    ///Whether or not the beacon is being wound down.
+    pub sunset: bool,
+    ///Whether or not every pending request and key deposit has been refunded.
+    pub complete: bool,
+}
+
+/// Privileged messages that can only be sent by the chain itself, allowing
+/// governance to step in without the owner key.
+#[cw_serde]
+pub enum SudoMsg {
+    SetPaused(SetPausedMsg),
+    UpdateOwner(SudoUpdateOwnerMsg),
+    ForceReturnDeposit(AdminReturnDepositMsg),
+}
+
+/// Replaces the owner of the beacon, discarding any pending ownership transfer.
+#[cw_serde]
+pub struct SudoUpdateOwnerMsg {
+    pub new_owner: String,
+}
+
 #[cw_serde]
 pub struct MigrateMsg {}
+
+/// Lets the believed gas price follow demand within the given bounds, or only
+/// change through `UpdateGasPrice` again when `adjustment` is `None`. This is
+/// only allowed to be called by the owner.
+#[cw_serde]
+pub struct SetGasPriceAdjustmentMsg {
+    pub adjustment: Option<GasPriceAdjustment>,
+}
+
+/// Lists the automatic adjustments of the gas price, oldest first.
+#[cw_serde]
+pub struct GasPriceHistoryQuery {
+    ///The id of the last record of the previous page.
+    pub start_after: Option<u64>,
+    pub limit: Option<u32>,
+}
+
+#[cw_serde]
+pub struct GasPriceHistoryResponse {
+    pub prices: Vec<GasPriceRecord>,
+}
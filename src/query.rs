@@ -1,16 +1,32 @@
-use cosmwasm_std::{Deps, Env, Order, StdError, StdResult, Uint128};
+use cosmwasm_std::{Coin, Deps, Env, Order, StdError, StdResult, Uint128};
 use cw_storage_plus::Bound;
-use ecvrf_rs::encode_hex;
+use ecvrf_rs::{encode_hex, PublicKey};
 use entropy_beacon_cosmos::{
-    beacon::{CalculateFeeQuery, CalculateFeeResponse},
+    beacon::{CalculateFeeResponse, UpdateConfigMsg},
     provide::{
         ActiveRequestsQuery, ActiveRequestsResponse, BeaconConfigResponse, KeyStatusQuery,
         KeyStatusResponse, LastEntropyResponse, DEFAULT_PAGINATION_LIMIT, MAX_PAGINATION_LIMIT,
     },
 };
 
-use crate::state::{CONFIG, STATE};
-use crate::utils::check_key;
+use crate::msg::{
+    AuditLogQuery, AuditLogResponse, CalculateFeeQuery, CreditQuery, CreditResponse,
+    ExportStateQuery, ExportedCredit, ExportedKey, ExportedLedger, ExportedReward, ExportedRole,
+    FeeDenom, FeeDenomsResponse, FeeGrantInfo, FeeGrantQuery, FeeGrantResponse, FeeGrantsQuery,
+    FeeGrantsResponse, FeeSplitResponse, GasPriceHistoryQuery, GasPriceHistoryResponse,
+    OwnershipResponse, PendingConfigChangesQuery, PendingConfigChangesResponse,
+    ProtocolRevenueResponse, RoleMembersQuery, RoleMembersResponse, RolesQuery, RolesResponse,
+    SolvencyQuery, SolvencyResponse, SponsorshipPoolQuery, SponsorshipPoolResponse,
+    SponsorshipSpendsQuery, SponsorshipSpendsResponse, StateExport, SunsetStatusResponse,
+    UnclaimedRewardsQuery, UnclaimedRewardsResponse, ValidateConfigUpdateResponse,
+    STATE_EXPORT_VERSION,
+};
+use crate::state::{
+    Config, PauseFlags, Role, AUDIT_LOG, CONFIG, CREDITS, FEE_DENOMS, FEE_GRANTS,
+    GAS_PRICE_HISTORY, LEDGERS, PENDING_CONFIG_CHANGES, PENDING_OWNER, REWARDS, ROLES,
+    SPONSORSHIP_SPENDS, STATE, WHITELISTED_KEYS,
+};
+use crate::utils::{balance, check_key, check_native_denom_change, has_role};
 use crate::{error::ContractError, state::ENTROPY_REQUESTS};
 
 /// Checks whether a key is whitelisted, and if so, whether enough blocks
@@ -85,23 +101,508 @@ pub fn beacon_config_query(deps: Deps) -> StdResult<BeaconConfigResponse> {
     Ok(CONFIG.load(deps.storage)?.into())
 }
 
+/// Calculates the fee for a request in the native denom, or in another denom
+/// that fees can be paid in at the price set for it.
 pub fn calculate_fee_query(deps: Deps, data: CalculateFeeQuery) -> StdResult<CalculateFeeResponse> {
     let cfg = CONFIG.load(deps.storage)?;
-    let state = STATE.load(deps.storage)?;
-    let gas_cost = Uint128::from(data.callback_gas_limit) * state.belief_gas_price;
-    let protocol_fee = Uint128::from(cfg.protocol_fee);
-    let total_fee = protocol_fee
-        + if cfg.subsidize_callbacks {
-            Uint128::zero()
-        } else {
-            gas_cost
-        };
+    let (protocol_fee, gas_price) = match &data.denom {
+        Some(denom) if denom != &cfg.native_denom => {
+            let pricing = FEE_DENOMS
+                .may_load(deps.storage, denom)?
+                .ok_or_else(|| StdError::generic_err(format!("Unsupported denom: {}", denom)))?;
+            (pricing.protocol_fee, pricing.gas_price)
+        }
+        _ => (cfg.protocol_fee, STATE.load(deps.storage)?.belief_gas_price),
+    };
+    let denom = data.denom.unwrap_or_else(|| cfg.native_denom.clone());
+    let gas_cost = Uint128::from(data.callback_gas_limit) * gas_price;
+    let protocol_fee = Uint128::from(protocol_fee);
+    let total_fee = protocol_fee + gas_cost - sponsored_gas(deps, &cfg, &denom, gas_cost)?;
     if total_fee > u64::MAX.into() {
         return Err(StdError::generic_err("Fee overflow"));
     }
     let total_fee = total_fee.u128() as u64;
     Ok(CalculateFeeResponse {
         fee: total_fee,
-        gas_price: state.belief_gas_price,
+        gas_price,
+    })
+}
+
+/// How much of a callback's gas cost the sponsorship pool pays for. While
+/// callbacks are subsidized, the pool pays for callbacks whose whole gas cost
+/// it can cover, and for none once it runs dry.
+pub(crate) fn sponsored_gas(
+    deps: Deps,
+    cfg: &Config,
+    denom: &str,
+    gas_cost: Uint128,
+) -> StdResult<Uint128> {
+    if !cfg.subsidize_callbacks {
+        return Ok(Uint128::zero());
+    }
+    let pool = LEDGERS
+        .may_load(deps.storage, denom)?
+        .unwrap_or_default()
+        .sponsorship;
+    Ok(if pool >= gas_cost {
+        gas_cost
+    } else {
+        Uint128::zero()
+    })
+}
+
+/// Returns the current owner of the contract, along with any pending owner.
+pub fn ownership_query(deps: Deps) -> StdResult<OwnershipResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(OwnershipResponse {
+        owner: cfg.owner,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+/// Returns every role held by an address, including those implied by ownership.
+pub fn roles_query(deps: Deps, data: RolesQuery) -> StdResult<RolesResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let addr = deps.api.addr_validate(&data.address)?;
+    let roles = Role::all()
+        .into_iter()
+        .filter(|role| has_role(&deps, &cfg, *role, &addr))
+        .collect();
+    Ok(RolesResponse { roles })
+}
+
+/// Lists the addresses that have explicitly been granted a role.
+pub fn role_members_query(deps: Deps, data: RoleMembersQuery) -> StdResult<RoleMembersResponse> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT);
+
+    let start_after = data
+        .start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let members = ROLES
+        .prefix(data.role.as_str())
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .collect::<StdResult<_>>()?;
+
+    Ok(RoleMembersResponse { members })
+}
+
+/// Lists the config changes that have been scheduled but not applied yet.
+pub fn pending_config_changes_query(
+    deps: Deps,
+    data: PendingConfigChangesQuery,
+) -> StdResult<PendingConfigChangesResponse> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT);
+
+    let start = data.start_after.map(Bound::exclusive);
+
+    let changes = PENDING_CONFIG_CHANGES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| item.map(|(_, c)| c))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingConfigChangesResponse { changes })
+}
+
+/// Returns which parts of the contract are currently paused.
+pub fn pause_flags_query(deps: Deps) -> StdResult<PauseFlags> {
+    Ok(CONFIG.load(deps.storage)?.pause_flags)
+}
+
+/// Lists the recorded privileged actions, oldest first.
+pub fn audit_log_query(deps: Deps, data: AuditLogQuery) -> StdResult<AuditLogResponse> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT);
+
+    let start = data.start_after.map(Bound::exclusive);
+
+    let entries = AUDIT_LOG
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| item.map(|(_, e)| e))
+        .collect::<StdResult<_>>()?;
+
+    Ok(AuditLogResponse { entries })
+}
+
+/// Lists the automatic adjustments of the gas price, oldest first.
+pub fn gas_price_history_query(
+    deps: Deps,
+    data: GasPriceHistoryQuery,
+) -> StdResult<GasPriceHistoryResponse> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT);
+
+    let start = data.start_after.map(Bound::exclusive);
+
+    let prices = GAS_PRICE_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| item.map(|(_, p)| p))
+        .collect::<StdResult<_>>()?;
+
+    Ok(GasPriceHistoryResponse { prices })
+}
+
+/// Dry-runs a config update against the current config, reporting every
+/// invariant that the resulting config would violate.
+pub fn validate_config_update_query(
+    deps: Deps,
+    data: UpdateConfigMsg,
+) -> StdResult<ValidateConfigUpdateResponse> {
+    let old_cfg = CONFIG.load(deps.storage)?;
+    let mut cfg = old_cfg.clone();
+    cfg.apply_update(data);
+    let mut errors: Vec<String> = cfg.violations().iter().map(|e| e.to_string()).collect();
+    if let Err(err) = check_native_denom_change(deps.storage, &old_cfg, &cfg) {
+        errors.push(err.to_string());
+    }
+    Ok(ValidateConfigUpdateResponse {
+        valid: errors.is_empty(),
+        errors,
+    })
+}
+
+/// Returns whether the beacon is being wound down, and whether every pending
+/// request and key deposit has been refunded yet.
+pub fn sunset_status_query(deps: Deps) -> StdResult<SunsetStatusResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(SunsetStatusResponse {
+        sunset: cfg.sunset,
+        complete: cfg.sunset
+            && ENTROPY_REQUESTS.is_empty(deps.storage)
+            && WHITELISTED_KEYS.is_empty(deps.storage),
     })
 }
+
+/// Exports a page of the beacon's state, in a format that `import_state`
+/// accepts.
+pub fn export_state_query(deps: Deps, data: ExportStateQuery) -> StdResult<StateExport> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT) as usize;
+
+    let start_key = data.start_after_key.map(|key| key.as_bytes().to_vec());
+    let keys = WHITELISTED_KEYS
+        .range(
+            deps.storage,
+            start_key.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(key, info)| ExportedKey {
+                public_key: PublicKey::from_bytes(&key),
+                info,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    let start_request = data
+        .start_after_request
+        .map(|id| Bound::exclusive(id.u128()));
+    let requests = ENTROPY_REQUESTS
+        .range(deps.storage, start_request, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, r)| r))
+        .collect::<StdResult<_>>()?;
+
+    let ledgers = LEDGERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, ledger)| ExportedLedger { denom, ledger }))
+        .collect::<StdResult<_>>()?;
+
+    let start_reward = data
+        .start_after_reward
+        .map(|(address, denom)| Ok::<_, StdError>((deps.api.addr_validate(&address)?, denom)))
+        .transpose()?;
+    let rewards = REWARDS
+        .range(
+            deps.storage,
+            start_reward
+                .as_ref()
+                .map(|(address, denom)| Bound::exclusive((address, denom.as_str()))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|((address, denom), amount)| ExportedReward {
+                address,
+                denom,
+                amount,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    let start_credit = data
+        .start_after_credit
+        .map(|(address, denom)| Ok::<_, StdError>((deps.api.addr_validate(&address)?, denom)))
+        .transpose()?;
+    let credits = CREDITS
+        .range(
+            deps.storage,
+            start_credit
+                .as_ref()
+                .map(|(address, denom)| Bound::exclusive((address, denom.as_str()))),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|((address, denom), amount)| ExportedCredit {
+                address,
+                denom,
+                amount,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    let start_grant = data
+        .start_after_grant
+        .map(|(grantee, sponsor, denom)| {
+            Ok::<_, StdError>((
+                deps.api.addr_validate(&grantee)?,
+                deps.api.addr_validate(&sponsor)?,
+                denom,
+            ))
+        })
+        .transpose()?;
+    let grants = FEE_GRANTS
+        .range(
+            deps.storage,
+            start_grant.as_ref().map(|(grantee, sponsor, denom)| {
+                Bound::exclusive((grantee, sponsor, denom.as_str()))
+            }),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, grant)| grant))
+        .collect::<StdResult<_>>()?;
+
+    let mut roles = vec![];
+    for role in Role::all() {
+        for address in ROLES
+            .prefix(role.as_str())
+            .keys(deps.storage, None, None, Order::Ascending)
+        {
+            roles.push(ExportedRole {
+                role,
+                address: address?,
+            });
+        }
+    }
+
+    let pending_config_changes = PENDING_CONFIG_CHANGES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<_>>()?;
+
+    let start_spend = data.start_after_spend.map(|id| Bound::exclusive(id.u128()));
+    let sponsorship_spends = SPONSORSHIP_SPENDS
+        .range(deps.storage, start_spend, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, spend)| spend))
+        .collect::<StdResult<_>>()?;
+
+    let start_gas_price = data.start_after_gas_price.map(Bound::exclusive);
+    let gas_price_history = GAS_PRICE_HISTORY
+        .range(deps.storage, start_gas_price, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect::<StdResult<_>>()?;
+
+    Ok(StateExport {
+        version: STATE_EXPORT_VERSION,
+        state: STATE.load(deps.storage)?,
+        config: CONFIG.load(deps.storage)?,
+        keys,
+        requests,
+        fee_denoms: fee_denoms(deps)?,
+        ledgers,
+        rewards,
+        credits,
+        grants,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+        roles,
+        pending_config_changes,
+        sponsorship_spends,
+        gas_price_history,
+    })
+}
+
+/// Returns the protocol revenue that has accrued and not been withdrawn yet.
+pub fn protocol_revenue_query(deps: Deps) -> StdResult<ProtocolRevenueResponse> {
+    let revenue = LEDGERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(denom, ledger)| Coin {
+                denom,
+                amount: ledger.revenue,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ProtocolRevenueResponse { revenue })
+}
+
+/// Reconciles the funds owed in a denom against the contract's balance,
+/// reporting any shortfall.
+pub fn solvency_query(deps: Deps, env: Env, data: SolvencyQuery) -> StdResult<SolvencyResponse> {
+    let denom = match data.denom {
+        Some(denom) => denom,
+        None => CONFIG.load(deps.storage)?.native_denom,
+    };
+    let ledger = LEDGERS.may_load(deps.storage, &denom)?.unwrap_or_default();
+    let balance = balance(&deps, &env, &denom)?;
+    let liabilities = ledger.liabilities();
+    Ok(SolvencyResponse {
+        denom,
+        balance,
+        deposits: ledger.deposits,
+        bounties: ledger.bounties,
+        revenue: ledger.revenue,
+        rewards: ledger.rewards,
+        credits: ledger.credits,
+        sponsorship: ledger.sponsorship,
+        grants: ledger.grants,
+        liabilities,
+        shortfall: liabilities.saturating_sub(balance),
+        solvent: balance >= liabilities,
+    })
+}
+
+/// Returns the split that is applied to each fulfilled request.
+pub fn fee_split_query(deps: Deps) -> StdResult<FeeSplitResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    Ok(FeeSplitResponse {
+        shares: cfg.fee_shares(),
+    })
+}
+
+/// Returns the rewards that an address has accrued and not claimed yet.
+pub fn unclaimed_rewards_query(
+    deps: Deps,
+    data: UnclaimedRewardsQuery,
+) -> StdResult<UnclaimedRewardsResponse> {
+    let addr = deps.api.addr_validate(&data.address)?;
+    let rewards = REWARDS
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<_>>()?;
+    Ok(UnclaimedRewardsResponse { rewards })
+}
+
+/// Returns the credit that an address has left to pay for requests with.
+pub fn credit_query(deps: Deps, data: CreditQuery) -> StdResult<CreditResponse> {
+    let addr = deps.api.addr_validate(&data.address)?;
+    let credits = CREDITS
+        .prefix(&addr)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<_>>()?;
+    Ok(CreditResponse { credits })
+}
+
+/// Returns what is left in the sponsorship pool in a denom.
+pub fn sponsorship_pool_query(
+    deps: Deps,
+    data: SponsorshipPoolQuery,
+) -> StdResult<SponsorshipPoolResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let ledger = LEDGERS.may_load(deps.storage, &denom)?.unwrap_or_default();
+    Ok(SponsorshipPoolResponse {
+        denom,
+        balance: ledger.sponsorship,
+        subsidize_callbacks: cfg.subsidize_callbacks,
+    })
+}
+
+/// Lists the callback gas paid for by the sponsorship pool, oldest request first.
+pub fn sponsorship_spends_query(
+    deps: Deps,
+    data: SponsorshipSpendsQuery,
+) -> StdResult<SponsorshipSpendsResponse> {
+    let limit = data
+        .limit
+        .unwrap_or(DEFAULT_PAGINATION_LIMIT)
+        .min(MAX_PAGINATION_LIMIT);
+
+    let start = data.start_after.map(|id| Bound::exclusive(id.u128()));
+
+    let spends = SPONSORSHIP_SPENDS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit as usize)
+        .map(|item| item.map(|(_, s)| s))
+        .collect::<StdResult<_>>()?;
+
+    Ok(SponsorshipSpendsResponse { spends })
+}
+
+/// Returns a single fee grant, along with what can currently be spent from it.
+pub fn fee_grant_query(deps: Deps, env: Env, data: FeeGrantQuery) -> StdResult<FeeGrantResponse> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let sponsor = deps.api.addr_validate(&data.sponsor)?;
+    let grantee = deps.api.addr_validate(&data.grantee)?;
+    let denom = data.denom.unwrap_or(cfg.native_denom);
+    let grant = FEE_GRANTS
+        .may_load(deps.storage, (&grantee, &sponsor, &denom))?
+        .map(|grant| FeeGrantInfo {
+            allowance: grant.allowance(env.block.height),
+            grant,
+        });
+    Ok(FeeGrantResponse { grant })
+}
+
+/// Returns every fee grant made to an address, along with what can currently
+/// be spent from each.
+pub fn fee_grants_query(
+    deps: Deps,
+    env: Env,
+    data: FeeGrantsQuery,
+) -> StdResult<FeeGrantsResponse> {
+    let grantee = deps.api.addr_validate(&data.grantee)?;
+    let grants = FEE_GRANTS
+        .sub_prefix(&grantee)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(_, grant)| FeeGrantInfo {
+                allowance: grant.allowance(env.block.height),
+                grant,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(FeeGrantsResponse { grants })
+}
+
+/// Returns every denom other than the native denom that fees can be paid in.
+pub fn fee_denoms_query(deps: Deps) -> StdResult<FeeDenomsResponse> {
+    Ok(FeeDenomsResponse {
+        denoms: fee_denoms(deps)?,
+    })
+}
+
+fn fee_denoms(deps: Deps) -> StdResult<Vec<FeeDenom>> {
+    FEE_DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, pricing)| FeeDenom { denom, pricing }))
+        .collect()
+}
@@ -1,9 +1,13 @@
 use cosmwasm_schema::cw_serde;
-use entropy_beacon_cosmos::{provide::ActiveRequestInfo, BeaconConfigResponse};
+use entropy_beacon_cosmos::{
+    beacon::UpdateConfigMsg, provide::ActiveRequestInfo, BeaconConfigResponse,
+};
 
-use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Empty, Uint128};
 use cw_storage_plus::{Item, Map};
 
+use crate::ContractError;
+
 #[cw_serde]
 pub struct State {
     ///The last submitted entropy.
@@ -12,10 +16,17 @@ pub struct State {
     pub belief_gas_price: Decimal,
     ///Current request id counter.
     pub cur_request_id: u128,
+    ///Current scheduled config change id counter.
+    pub cur_config_change_id: u64,
+    ///The number of requests waiting to be fulfilled.
+    pub pending_requests: u64,
+    ///Current gas price record id counter.
+    pub cur_gas_price_record_id: u64,
 }
 #[cw_serde]
 pub struct Config {
-    pub owner: Addr,
+    ///The owner of the contract, or `None` if ownership has been renounced.
+    pub owner: Option<Addr>,
     ///The amount of tokens that must be deposited to whitelist a new public key.
     pub whitelist_deposit_amt: Uint128,
     ///The amount of the deposit that unlocks with each submission of entropy.
@@ -28,14 +39,240 @@ pub struct Config {
     pub submitter_share: Decimal,
     ///The native currency of the target chain.
     pub native_denom: String,
-    ///Whether or not the contract is paused.
-    pub paused: bool,
+    ///Which parts of the contract are paused.
+    pub pause_flags: PauseFlags,
     ///Whether or not the contract is in permissioned mode.
     pub permissioned: bool,
     ///Whether or not the contract is in test mode.
     pub test_mode: bool,
     ///Whether or not callback subsidization is enabled.
     pub subsidize_callbacks: bool,
+    ///The time, in blocks, that config changes must wait before they can be applied.
+    pub config_timelock: u64,
+    ///Whether or not the contract is being wound down.
+    pub sunset: bool,
+    ///The beacon that pending requests are handed off to, if any.
+    pub successor: Option<Addr>,
+    ///The beacon that is allowed to hand off its pending requests to this one, if any.
+    pub predecessor: Option<Addr>,
+    ///How the bounty of each fulfilled request is split, or `None` to pay
+    ///`submitter_share` to the submitter and keep the rest as protocol revenue.
+    pub fee_split: Option<Vec<FeeShare>>,
+    ///What to do with coins sent in denoms other than the native denom.
+    pub foreign_denoms: ForeignDenomPolicy,
+    ///How the believed gas price follows demand, or `None` if it only
+    ///changes through `UpdateGasPrice`.
+    pub gas_price_adjustment: Option<GasPriceAdjustment>,
+}
+
+/// Bounds on how the believed gas price follows demand. The price is adjusted
+/// on each submission by how far the queue of pending requests is from a target.
+#[cw_serde]
+pub struct GasPriceAdjustment {
+    ///The number of pending requests at which the gas price holds steady.
+    pub target_queue_depth: u64,
+    ///The lowest that the gas price is adjusted to, which must not be zero.
+    pub min_gas_price: Decimal,
+    ///The highest that the gas price is adjusted to.
+    pub max_gas_price: Decimal,
+    ///The most that a single submission changes the gas price by, as a
+    ///fraction of the current price.
+    pub max_change: Decimal,
+}
+
+impl GasPriceAdjustment {
+    /// The gas price that follows `price` when `queue_depth` requests are pending.
+    /// The change grows with the distance from the target, and is at its
+    /// largest once the queue is empty or twice as deep as the target.
+    pub fn next_price(&self, price: Decimal, queue_depth: u64) -> Decimal {
+        let target = self.target_queue_depth;
+        let distance = Decimal::from_ratio(queue_depth.abs_diff(target), target);
+        let step = price * self.max_change * distance.min(Decimal::one());
+        let next = if queue_depth > target {
+            price + step
+        } else {
+            price - step
+        };
+        next.max(self.min_gas_price).min(self.max_gas_price)
+    }
+}
+
+/// The believed gas price after an automatic adjustment.
+#[cw_serde]
+pub struct GasPriceRecord {
+    ///The id of the record.
+    pub id: u64,
+    ///The block that the price was adjusted on.
+    pub height: u64,
+    ///The adjusted gas price.
+    pub gas_price: Decimal,
+    ///The number of pending requests that the price was adjusted for.
+    pub queue_depth: u64,
+}
+
+/// What to do with coins of denoms that the beacon does not accept.
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum ForeignDenomPolicy {
+    ///Fail the message.
+    #[default]
+    Reject,
+    ///Accept the message, and send the coins back.
+    Refund,
+}
+
+/// A party that receives part of the bounty of each fulfilled request.
+#[cw_serde]
+pub enum FeeRecipient {
+    ///The key holder submitting the entropy.
+    Submitter,
+    ///Kept by the beacon as protocol revenue.
+    Protocol,
+    ///Sent to a treasury address.
+    Treasury { address: Addr },
+    ///Burned.
+    Burn,
+    ///Sent to the referrer of each request. Kept by the beacon as protocol
+    ///revenue for requests without one.
+    Referrer,
+}
+
+impl FeeRecipient {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeeRecipient::Submitter => "submitter",
+            FeeRecipient::Protocol => "protocol",
+            FeeRecipient::Treasury { .. } => "treasury",
+            FeeRecipient::Burn => "burn",
+            FeeRecipient::Referrer => "referrer",
+        }
+    }
+}
+
+/// The share of each bounty that goes to a single recipient.
+#[cw_serde]
+pub struct FeeShare {
+    pub recipient: FeeRecipient,
+    pub share: Decimal,
+}
+
+/// Independent switches for pausing each group of interactions.
+#[cw_serde]
+#[derive(Default)]
+pub struct PauseFlags {
+    ///Whether or not new entropy requests are paused.
+    pub requests: bool,
+    ///Whether or not entropy submissions are paused.
+    pub submissions: bool,
+    ///Whether or not whitelisting new keys is paused.
+    pub whitelisting: bool,
+    ///Whether or not deposit withdrawals are paused.
+    pub withdrawals: bool,
+}
+
+impl PauseFlags {
+    /// Sets every flag to the same value.
+    pub fn all(paused: bool) -> Self {
+        PauseFlags {
+            requests: paused,
+            submissions: paused,
+            whitelisting: paused,
+            withdrawals: paused,
+        }
+    }
+
+    /// Whether or not any part of the contract is paused.
+    pub fn any(&self) -> bool {
+        self.requests || self.submissions || self.whitelisting || self.withdrawals
+    }
+}
+
+impl Config {
+    /// Lists every invariant that this config violates.
+    pub fn violations(&self) -> Vec<ContractError> {
+        let mut violations = vec![];
+        if self.submitter_share > Decimal::one() {
+            violations.push(ContractError::InvalidSubmitterShare {});
+        }
+        if self.native_denom.is_empty() {
+            violations.push(ContractError::EmptyNativeDenom {});
+        }
+        if self.refund_increment_amt > self.whitelist_deposit_amt {
+            violations.push(ContractError::RefundIncrementExceedsDeposit {});
+        }
+        if let Some(split) = &self.fee_split {
+            let total = split
+                .iter()
+                .try_fold(Decimal::zero(), |total, s| total.checked_add(s.share));
+            if total != Ok(Decimal::one()) {
+                violations.push(ContractError::InvalidFeeSplit {});
+            }
+        }
+        if let Some(adjustment) = &self.gas_price_adjustment {
+            // Each step is proportional to the price, so a price of zero
+            // could never rise again.
+            if adjustment.target_queue_depth == 0
+                || adjustment.min_gas_price.is_zero()
+                || adjustment.min_gas_price > adjustment.max_gas_price
+                || adjustment.max_change > Decimal::one()
+            {
+                violations.push(ContractError::InvalidGasPriceAdjustment {});
+            }
+        }
+        violations
+    }
+
+    /// The split that is applied to each fulfilled request.
+    pub fn fee_shares(&self) -> Vec<FeeShare> {
+        match &self.fee_split {
+            Some(split) => split.clone(),
+            None => vec![
+                FeeShare {
+                    recipient: FeeRecipient::Submitter,
+                    share: self.submitter_share,
+                },
+                FeeShare {
+                    recipient: FeeRecipient::Protocol,
+                    share: Decimal::one() - self.submitter_share,
+                },
+            ],
+        }
+    }
+
+    /// Ensures that this config does not violate any invariant.
+    pub fn validate(&self) -> Result<(), ContractError> {
+        match self.violations().into_iter().next() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies every field that is set in an `UpdateConfigMsg`.
+    pub fn apply_update(&mut self, data: UpdateConfigMsg) {
+        self.whitelist_deposit_amt = data
+            .whitelist_deposit_amt
+            .unwrap_or(self.whitelist_deposit_amt);
+        self.refund_increment_amt = data
+            .refund_increment_amt
+            .unwrap_or(self.refund_increment_amt);
+        self.key_activation_delay = data
+            .key_activation_delay
+            .unwrap_or(self.key_activation_delay);
+        self.protocol_fee = data.protocol_fee.unwrap_or(self.protocol_fee);
+        self.submitter_share = data
+            .submitter_share
+            .map(Decimal::percent)
+            .unwrap_or(self.submitter_share);
+        self.native_denom = data
+            .native_denom
+            .unwrap_or_else(|| self.native_denom.clone());
+        self.pause_flags = data
+            .paused
+            .map(PauseFlags::all)
+            .unwrap_or_else(|| self.pause_flags.clone());
+        self.permissioned = data.permissioned.unwrap_or(self.permissioned);
+        self.subsidize_callbacks = data.subsidize_callbacks.unwrap_or(self.subsidize_callbacks);
+    }
 }
 
 impl From<Config> for BeaconConfigResponse {
@@ -45,9 +282,14 @@ impl From<Config> for BeaconConfigResponse {
             refund_increment_amt: val.refund_increment_amt,
             key_activation_delay: val.key_activation_delay,
             protocol_fee: val.protocol_fee,
-            submitter_share: val.submitter_share,
+            submitter_share: val
+                .fee_shares()
+                .iter()
+                .filter(|s| s.recipient == FeeRecipient::Submitter)
+                .map(|s| s.share)
+                .sum(),
             native_denom: val.native_denom,
-            paused: val.paused,
+            paused: val.pause_flags.any(),
             permissioned: val.permissioned,
             test_mode: val.test_mode,
             subsidize_callbacks: val.subsidize_callbacks,
@@ -72,6 +314,16 @@ pub struct EntropyRequest {
     pub submitted_block_height: u64,
     ///The amount of tokens left after subtracting the requested gas.
     pub submitted_bounty_amount: Uint128,
+    ///The part of the bounty that the sponsorship pool paid for, which goes
+    ///back to the pool if the request is refunded or forwarded.
+    pub subsidy: Uint128,
+    ///The denom that the bounty was paid in.
+    pub denom: String,
+    ///The address that referred the request, if any.
+    pub referrer: Option<Addr>,
+    ///The fee grant that paid for the request, which the bounty goes back to
+    ///if the request is refunded.
+    pub fee_grant: Option<FeeGrantKey>,
 }
 
 impl EntropyRequest {
@@ -87,16 +339,251 @@ impl EntropyRequest {
     }
 }
 
+/// A config change that has been scheduled by the owner, and can be applied
+/// by anyone once `effective_height` has been reached.
+#[cw_serde]
+pub struct PendingConfigChange {
+    ///The id of the change.
+    pub id: u64,
+    ///The fields of the config that will be updated.
+    pub update: UpdateConfigMsg,
+    ///The new config timelock, if it is being shortened.
+    pub config_timelock: Option<u64>,
+    ///The block that the change was scheduled on.
+    pub scheduled_height: u64,
+    ///The block from which the change can be applied.
+    pub effective_height: u64,
+}
+
+/// A record of a privileged action, kept so that the history of the beacon's
+/// configuration and deposits can be reconstructed from contract state.
+#[cw_serde]
+pub struct AuditEntry {
+    ///The id of the entry.
+    pub id: u64,
+    ///The address that performed the action, or `None` for chain governance.
+    pub actor: Option<Addr>,
+    ///The block that the action was performed on.
+    pub height: u64,
+    ///The name of the action that was performed.
+    pub action: String,
+    ///Every value that was changed by the action.
+    pub changes: Vec<AuditChange>,
+}
+
+/// A single value changed by a privileged action. Values are JSON encoded,
+/// with `null` representing a value that did not exist.
+#[cw_serde]
+pub struct AuditChange {
+    pub field: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
 #[cw_serde]
 pub struct KeyInfo {
     pub holder: Addr,
     pub deposit_amount: Uint128,
     pub refundable_amount: Uint128,
     pub creation_height: u64,
+    ///Where rewards and deposit refunds are sent, if not to the holder.
+    pub payout_address: Option<Addr>,
+    ///The denom that the deposit was paid in.
+    pub deposit_denom: String,
+}
+
+impl KeyInfo {
+    /// The address that receives rewards and deposit refunds for this key.
+    pub fn payout_address(&self) -> &Addr {
+        self.payout_address.as_ref().unwrap_or(&self.holder)
+    }
+}
+
+/// The funds held by the contract in a single denom, split by who they are owed to.
+#[cw_serde]
+#[derive(Default)]
+pub struct Ledger {
+    ///Deposits made to whitelist keys, owed to the key holders.
+    pub deposits: Uint128,
+    ///Bounties of pending requests, owed to whoever fulfills them.
+    pub bounties: Uint128,
+    ///Protocol revenue that the treasury is free to withdraw.
+    pub revenue: Uint128,
+    ///Rewards accrued by submitters that have not been claimed yet.
+    pub rewards: Uint128,
+    ///Credit prepaid by requesters that has not been spent yet.
+    pub credits: Uint128,
+    ///Funds given to pay for the callback gas of requests while callbacks
+    ///are subsidized, that have not been spent yet.
+    pub sponsorship: Uint128,
+    ///Funds deposited by sponsors for fee grants that have not been spent yet.
+    pub grants: Uint128,
+}
+
+impl Ledger {
+    /// Every amount that the contract must be able to pay out.
+    pub fn liabilities(&self) -> Uint128 {
+        self.deposits
+            + self.bounties
+            + self.revenue
+            + self.rewards
+            + self.credits
+            + self.sponsorship
+            + self.grants
+    }
+}
+
+/// Funds that a sponsor has set aside to pay the fees of requests made by a
+/// single address, or calling back to it from requesters that the sponsor
+/// has allowed.
+#[cw_serde]
+pub struct FeeGrant {
+    ///The address that deposited the funds.
+    pub sponsor: Addr,
+    ///The requester or callback address whose requests are paid for.
+    pub grantee: Addr,
+    ///Requesters other than the grantee whose requests calling back to the
+    ///grantee are paid for.
+    pub callback_requesters: Vec<Addr>,
+    ///The denom that fees are paid in.
+    pub denom: String,
+    ///The deposited funds that have not been spent or returned yet.
+    pub balance: Uint128,
+    ///The most that can be spent over the lifetime of the grant, if limited.
+    pub spend_limit: Option<Uint128>,
+    ///The most that can be spent on a single request, if limited.
+    pub per_request_limit: Option<Uint128>,
+    ///The block from which the grant can no longer be used, if any.
+    pub expires_at: Option<u64>,
+    ///The amount spent on requests so far.
+    pub spent: Uint128,
+}
+
+impl FeeGrant {
+    /// Whether or not the grant can no longer be used at a block height.
+    pub fn is_expired(&self, height: u64) -> bool {
+        matches!(self.expires_at, Some(expiry) if height >= expiry)
+    }
+
+    /// The most that a single request can be paid from the grant at a block height.
+    pub fn allowance(&self, height: u64) -> Uint128 {
+        if self.is_expired(height) {
+            return Uint128::zero();
+        }
+        let mut allowance = self.balance;
+        if let Some(limit) = self.spend_limit {
+            allowance = allowance.min(limit.saturating_sub(self.spent));
+        }
+        if let Some(limit) = self.per_request_limit {
+            allowance = allowance.min(limit);
+        }
+        allowance
+    }
+}
+
+/// Identifies a fee grant in the denom of the request that it paid for.
+#[cw_serde]
+pub struct FeeGrantKey {
+    pub grantee: Addr,
+    pub sponsor: Addr,
+}
+
+/// The callback gas of a request that was paid for by the sponsorship pool.
+#[cw_serde]
+pub struct SponsorshipSpend {
+    ///The id of the request.
+    pub request_id: Uint128,
+    ///The block that the request was made on.
+    pub height: u64,
+    ///The denom that the gas was paid in.
+    pub denom: String,
+    ///The amount that was paid.
+    pub amount: Uint128,
+}
+
+/// The price of requests paid in a denom other than the native denom.
+/// Cw20 tokens are priced under the denom `cw20:<token address>`.
+#[cw_serde]
+pub struct DenomPricing {
+    ///The fee that the protocol charges on top of the requested gas fees, in this denom.
+    pub protocol_fee: u64,
+    ///The price of callback gas, in this denom.
+    pub gas_price: Decimal,
+    ///The deposit required to whitelist a key in this denom, if deposits are accepted in it.
+    pub deposit: Option<DenomDeposit>,
+}
+
+/// What whitelisting a key costs in a denom.
+#[cw_serde]
+pub struct DenomDeposit {
+    ///The amount that must be deposited to whitelist a key.
+    pub whitelist_deposit_amt: Uint128,
+    ///How much of the deposit is unlocked by each submission.
+    pub refund_increment_amt: Uint128,
+}
+
+/// Privileges that the owner can delegate to other addresses.
+/// The owner implicitly holds every role.
+#[cw_serde]
+#[derive(Copy, Eq)]
+pub enum Role {
+    ///Can update the believed gas price.
+    GasPriceUpdater,
+    ///Can pause and unpause the beacon.
+    Pauser,
+    ///Can whitelist keys in permissioned mode, and return key deposits.
+    KeyManager,
+    ///Can manage the funds held in the protocol treasury.
+    Treasurer,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::GasPriceUpdater => "gas_price_updater",
+            Role::Pauser => "pauser",
+            Role::KeyManager => "key_manager",
+            Role::Treasurer => "treasurer",
+        }
+    }
+
+    pub fn all() -> [Role; 4] {
+        [
+            Role::GasPriceUpdater,
+            Role::Pauser,
+            Role::KeyManager,
+            Role::Treasurer,
+        ]
+    }
 }
 
 pub const STATE: Item<State> = Item::new("state");
 pub const CONFIG: Item<Config> = Item::new("config");
+///The address that has been proposed as the next owner, pending acceptance.
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+///Role grants, keyed by `(role, address)`.
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
 pub const WHITELISTED_KEYS: Map<&[u8], KeyInfo> = Map::new("whitelisted_keys");
 
-pub const ENTROPY_REQUESTS: Map<u128, EntropyRequest> = Map::new("entropy_requests");
\ No newline at end of file
+pub const ENTROPY_REQUESTS: Map<u128, EntropyRequest> = Map::new("entropy_requests");
+pub const PENDING_CONFIG_CHANGES: Map<u64, PendingConfigChange> =
+    Map::new("pending_config_changes");
+pub const AUDIT_LOG: Map<u64, AuditEntry> = Map::new("audit_log");
+///The funds that the contract owes, per denom.
+pub const LEDGERS: Map<&str, Ledger> = Map::new("ledgers");
+///Denoms other than the native denom that fees can be paid in, and their prices.
+pub const FEE_DENOMS: Map<&str, DenomPricing> = Map::new("fee_denoms");
+///Unclaimed submitter rewards, keyed by `(payout address, denom)` of the keys
+///that earned them.
+pub const REWARDS: Map<(&Addr, &str), Uint128> = Map::new("rewards");
+///Prepaid requester credit, keyed by `(address, denom)`.
+pub const CREDITS: Map<(&Addr, &str), Uint128> = Map::new("credits");
+///Callback gas paid for by the sponsorship pool, keyed by request id.
+pub const SPONSORSHIP_SPENDS: Map<u128, SponsorshipSpend> = Map::new("sponsorship_spends");
+///Fee grants, keyed by `(grantee, sponsor, denom)`.
+pub const FEE_GRANTS: Map<(&Addr, &Addr, &str), FeeGrant> = Map::new("fee_grants");
+///Automatic gas price adjustments, keyed by record id in the order they were made.
+pub const GAS_PRICE_HISTORY: Map<u64, GasPriceRecord> = Map::new("gas_price_history");
+///Whether or not a state export can still be imported. Only set on fresh
+///beacons, and cleared once the owner finalizes the import.
+pub const IMPORT_OPEN: Item<bool> = Item::new("import_open");
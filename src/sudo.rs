@@ -0,0 +1,53 @@
+use cosmwasm_std::{DepsMut, Env, Response};
+use entropy_beacon_cosmos::provide::AdminReturnDepositMsg;
+
+use crate::{
+    error::ContractError,
+    execute::{return_full_deposit, update_pause_flags},
+    msg::{SetPausedMsg, SudoUpdateOwnerMsg},
+    state::{CONFIG, PENDING_OWNER},
+    utils::{audit_change, config_changes, record_audit},
+};
+
+/// Pauses or unpauses parts of the contract on behalf of chain governance.
+pub fn set_paused(deps: DepsMut, env: Env, data: SetPausedMsg) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    update_pause_flags(deps, &env, None, cfg, data, "sudo_set_paused")
+}
+
+/// Replaces the owner of the contract on behalf of chain governance,
+/// discarding any pending ownership transfer.
+pub fn update_owner(
+    deps: DepsMut,
+    env: Env,
+    data: SudoUpdateOwnerMsg,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let new_owner = deps.api.addr_validate(&data.new_owner)?;
+
+    let old_cfg = cfg.clone();
+    cfg.owner = Some(new_owner.clone());
+    CONFIG.save(deps.storage, &cfg)?;
+    let pending_owner = PENDING_OWNER.may_load(deps.storage)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    let mut changes = config_changes(&old_cfg, &cfg)?;
+    if pending_owner.is_some() {
+        changes.push(audit_change("pending_owner", &pending_owner, &None)?);
+    }
+    record_audit(deps.storage, &env, None, "sudo_update_owner", changes)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_update_owner")
+        .add_attribute("owner", new_owner))
+}
+
+/// Unwhitelists a key and returns its full deposit on behalf of chain
+/// governance. Unlike `admin_return_deposit`, this ignores pause flags.
+pub fn force_return_deposit(
+    deps: DepsMut,
+    env: Env,
+    data: AdminReturnDepositMsg,
+) -> Result<Response, ContractError> {
+    return_full_deposit(deps, &env, None, data.key, "sudo_force_return_deposit")
+}
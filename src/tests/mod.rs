@@ -1,12 +1,17 @@
 use cosmwasm_std::{
-    testing::{mock_env, mock_info},
-    DepsMut, Response, Uint128, Decimal,
+    coins,
+    testing::{
+        mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    },
+    to_binary, Addr, DepsMut, Empty, OwnedDeps, Response, Uint128, Decimal,
 };
 use ecvrf_rs::{PublicKey, SecretKey};
 use entropy_beacon_cosmos::msg::InstantiateMsg;
 
 use crate::contract::instantiate;
+use crate::msg::RequestEntropyMsg;
 
+#[allow(clippy::get_first)]
 mod test_instantiate;
 mod test_reclaim_deposit;
 mod test_admin_return_deposit;
@@ -15,6 +20,31 @@ mod test_submit_entropy;
 mod test_whitelist_key;
 mod test_calculate_gas;
 mod test_active_request_query;
+mod test_ownership;
+mod test_roles;
+mod test_config_timelock;
+mod test_pause_flags;
+mod test_sudo;
+mod test_audit_log;
+mod test_config_validation;
+mod test_exit_test_mode;
+mod test_migrate;
+mod test_sunset;
+mod test_forward_requests;
+mod test_state_export;
+mod test_protocol_revenue;
+mod test_solvency;
+mod test_fee_split;
+mod test_rewards;
+mod test_payout_address;
+mod test_max_fee;
+mod test_foreign_denoms;
+mod test_fee_denoms;
+mod test_cw20;
+mod test_credit;
+mod test_sponsorship;
+mod test_fee_grants;
+mod test_gas_price_adjustment;
 
 pub fn test_pk() -> PublicKey {
     let pk =
@@ -29,6 +59,35 @@ pub fn test_sk() -> SecretKey {
     SecretKey::from_slice(sk.as_slice())
 }
 
+pub fn other_sk() -> SecretKey {
+    SecretKey::from_slice(&[1; 32])
+}
+
+pub fn other_pk() -> PublicKey {
+    other_sk().extract_public_key_and_scalar().unwrap().0
+}
+
+/// Mocked dependencies in which the contract already holds plenty of the
+/// native denom, standing in for the funds that mocked messages would send it.
+pub fn funded_dependencies() -> OwnedDeps<MockStorage, MockApi, MockQuerier, Empty> {
+    mock_dependencies_with_balance(&coins(1_000_000_000, "uluna"))
+}
+
+/// A request for a 1000 gas callback, which costs 250 at the default gas price
+/// including the protocol fee. Tests override the fields that they need.
+pub fn request_msg() -> RequestEntropyMsg {
+    RequestEntropyMsg {
+        callback_gas_limit: 1000,
+        callback_address: Addr::unchecked("callback_address"),
+        callback_msg: to_binary("callback_msg").unwrap(),
+        max_fee: None,
+        bounty: None,
+        refund_address: None,
+        pay_from_credit: None,
+        referrer: None,
+    }
+}
+
 pub fn default_instantiate(deps: DepsMut) -> Response {
     let msg = InstantiateMsg {
         whitelist_deposit_amt: Uint128::from(1000u128),
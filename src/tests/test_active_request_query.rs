@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     coin,
-    testing::{mock_dependencies, mock_env, mock_info}, Uint128,
+    testing::{mock_env, mock_info}, Uint128,
 };
 use ecvrf_rs::Proof;
 use entropy_beacon_cosmos::provide::{ActiveRequestsQuery, SubmitEntropyMsg};
@@ -9,14 +9,14 @@ use crate::{
     execute,
     query::active_requests_query,
     tests::{
-        test_sk,
+        funded_dependencies, test_sk,
         test_submit_entropy::{request_entropy, setup_contract},
     },
 };
 
 #[test]
 fn pagination_works() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -70,7 +70,7 @@ fn pagination_works() {
 
 #[test]
 fn pagination_works_when_exceeding() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -114,7 +114,7 @@ fn pagination_works_when_exceeding() {
 
 #[test]
 fn max_pagination_limit_obeyed() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -138,7 +138,7 @@ fn max_pagination_limit_obeyed() {
 
 #[test]
 fn works_with_removed_elements() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -0,0 +1,165 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_dependencies, mock_env, mock_info},
+    Addr, Decimal,
+};
+use entropy_beacon_cosmos::{
+    beacon::UpdateConfigMsg,
+    provide::{AdminReturnDepositMsg, WhitelistPublicKeyMsg},
+};
+
+use crate::{
+    contract::sudo,
+    execute,
+    msg::{AuditLogQuery, SetPausedMsg, SudoMsg},
+    query,
+    state::AuditChange,
+    utils::empty_config_update,
+};
+
+use super::{default_instantiate, test_pk};
+
+#[test]
+fn records_privileged_actions() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("submitter", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        protocol_fee: Some(200),
+        whitelist_deposit_amt: Some(2000u128.into()),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.height += 1;
+    execute::update_gas_price(deps.as_mut(), env.clone(), info.clone(), Decimal::one()).unwrap();
+
+    env.block.height += 1;
+    let msg = AdminReturnDepositMsg { key: test_pk() };
+    execute::admin_return_deposit(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query::audit_log_query(
+        deps.as_ref(),
+        AuditLogQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.entries.len(), 3);
+
+    let entry = &res.entries[0];
+    assert_eq!(entry.id, 0);
+    assert_eq!(entry.action, "update_config");
+    assert_eq!(entry.actor, Some(Addr::unchecked("creator")));
+    assert_eq!(entry.height, env.block.height - 2);
+    assert_eq!(
+        entry.changes,
+        vec![
+            AuditChange {
+                field: "whitelist_deposit_amt".to_string(),
+                old_value: "\"1000\"".to_string(),
+                new_value: "\"2000\"".to_string(),
+            },
+            AuditChange {
+                field: "protocol_fee".to_string(),
+                old_value: "100".to_string(),
+                new_value: "200".to_string(),
+            },
+        ]
+    );
+
+    let entry = &res.entries[1];
+    assert_eq!(entry.action, "update_gas_price");
+    assert_eq!(entry.height, env.block.height - 1);
+    assert_eq!(
+        entry.changes,
+        vec![AuditChange {
+            field: "belief_gas_price".to_string(),
+            old_value: "\"0.15\"".to_string(),
+            new_value: "\"1\"".to_string(),
+        }]
+    );
+
+    let entry = &res.entries[2];
+    assert_eq!(entry.action, "reclaim_deposit");
+    assert_eq!(entry.changes.len(), 1);
+    assert_eq!(
+        entry.changes[0].field,
+        format!("whitelisted_keys.{}", test_pk())
+    );
+    assert_eq!(entry.changes[0].new_value, "null");
+}
+
+#[test]
+fn records_governance_actions() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = SudoMsg::SetPaused(SetPausedMsg {
+        requests: Some(true),
+        submissions: None,
+        whitelisting: None,
+        withdrawals: None,
+    });
+    sudo(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let res = query::audit_log_query(
+        deps.as_ref(),
+        AuditLogQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.entries.len(), 1);
+    assert_eq!(res.entries[0].actor, None);
+    assert_eq!(res.entries[0].action, "sudo_set_paused");
+    assert_eq!(res.entries[0].changes[0].field, "pause_flags");
+}
+
+#[test]
+fn paginates() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("creator", &[]);
+    for i in 1..=15u64 {
+        let price = Decimal::percent(i);
+        execute::update_gas_price(deps.as_mut(), mock_env(), info.clone(), price).unwrap();
+    }
+
+    let res = query::audit_log_query(
+        deps.as_ref(),
+        AuditLogQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+
+    let res = query::audit_log_query(
+        deps.as_ref(),
+        AuditLogQuery {
+            start_after: Some(9),
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.entries.iter().map(|e| e.id).collect::<Vec<_>>(),
+        (10..15).collect::<Vec<_>>()
+    );
+}
@@ -5,7 +5,7 @@ use cosmwasm_std::{
 };
 use entropy_beacon_cosmos::{beacon::CalculateFeeQuery, msg::InstantiateMsg};
 
-use crate::{execute, query, tests::default_instantiate, ContractError, contract::instantiate};
+use crate::{execute, msg::FundSponsorshipMsg, query, tests::default_instantiate, ContractError, contract::instantiate};
 
 #[test]
 fn calculates_correctly() {
@@ -16,7 +16,7 @@ fn calculates_correctly() {
         callback_gas_limit: 1000,
     };
 
-    let res = query::calculate_fee_query(deps.as_ref(), msg);
+    let res = query::calculate_fee_query(deps.as_ref(), msg.into());
     assert!(res.is_ok());
 
     let res = res.unwrap();
@@ -36,7 +36,7 @@ fn updates_belief_price() {
         callback_gas_limit: 1000,
     };
 
-    let res = query::calculate_fee_query(deps.as_ref(), msg.clone());
+    let res = query::calculate_fee_query(deps.as_ref(), msg.clone().into());
     assert!(res.is_ok());
 
     let res = res.unwrap();
@@ -46,7 +46,7 @@ fn updates_belief_price() {
     let res = execute::update_gas_price(deps.as_mut(), env, info, Decimal::percent(500));
     assert!(res.is_ok());
 
-    let res = query::calculate_fee_query(deps.as_ref(), msg);
+    let res = query::calculate_fee_query(deps.as_ref(), msg.into());
     assert!(res.is_ok());
 
     let res = res.unwrap();
@@ -87,13 +87,17 @@ fn subsidization_works() {
     let env = mock_env();
     let info = mock_info("creator", vec![].as_slice());
 
-    instantiate(deps.as_mut(), env, info, msg).unwrap();
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Gas is only subsidized while the sponsorship pool can cover it.
+    let info = mock_info("sponsor", &[coin(150, "uluna")]);
+    execute::fund_sponsorship(deps.as_mut(), env, info, FundSponsorshipMsg {}).unwrap();
 
     let msg = CalculateFeeQuery {
         callback_gas_limit: 1000,
     };
 
-    let res = query::calculate_fee_query(deps.as_ref(), msg);
+    let res = query::calculate_fee_query(deps.as_ref(), msg.into());
     assert!(res.is_ok());
 
     let res = res.unwrap();
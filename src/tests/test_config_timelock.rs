@@ -0,0 +1,197 @@
+use cosmwasm_std::{
+    testing::{mock_dependencies, mock_env, mock_info},
+    DepsMut, Env,
+};
+use entropy_beacon_cosmos::beacon::UpdateConfigMsg;
+
+use crate::{
+    execute,
+    msg::{
+        ApplyConfigChangeMsg, CancelConfigChangeMsg, PendingConfigChangesQuery,
+        UpdateConfigTimelockMsg,
+    },
+    query,
+    utils::empty_config_update,
+    ContractError,
+};
+
+use super::default_instantiate;
+
+fn set_timelock(deps: DepsMut, env: Env, blocks: u64) {
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigTimelockMsg { blocks };
+    execute::update_config_timelock(deps, env, info, msg).unwrap();
+}
+
+fn raise_protocol_fee(deps: DepsMut, env: Env) {
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        protocol_fee: Some(500),
+        ..empty_config_update()
+    };
+    execute::update_config(deps, env, info, msg).unwrap();
+}
+
+#[test]
+fn applies_immediately_without_timelock() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    raise_protocol_fee(deps.as_mut(), env);
+
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.protocol_fee, 500);
+}
+
+#[test]
+fn schedules_changes_with_timelock() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+    set_timelock(deps.as_mut(), env.clone(), 10);
+
+    raise_protocol_fee(deps.as_mut(), env.clone());
+
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.protocol_fee, 100);
+
+    let res = query::pending_config_changes_query(
+        deps.as_ref(),
+        PendingConfigChangesQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.changes.len(), 1);
+    assert_eq!(res.changes[0].update.protocol_fee, Some(500));
+    assert_eq!(res.changes[0].effective_height, env.block.height + 10);
+
+    let info = mock_info("anyone", &[]);
+    env.block.height += 9;
+    let res = execute::apply_config_change(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ApplyConfigChangeMsg { id: 0 },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::ConfigChangeNotReady {
+            effective_height: env.block.height + 1
+        }
+    );
+
+    env.block.height += 1;
+    execute::apply_config_change(deps.as_mut(), env, info, ApplyConfigChangeMsg { id: 0 }).unwrap();
+
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.protocol_fee, 500);
+
+    let res = query::pending_config_changes_query(
+        deps.as_ref(),
+        PendingConfigChangesQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert!(res.changes.is_empty());
+}
+
+#[test]
+fn pausing_is_instant() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+    set_timelock(deps.as_mut(), env.clone(), 10);
+
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        paused: Some(true),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env, info, msg).unwrap();
+
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert!(cfg.paused);
+
+    let res = query::pending_config_changes_query(
+        deps.as_ref(),
+        PendingConfigChangesQuery {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert!(res.changes.is_empty());
+}
+
+#[test]
+fn cancels_scheduled_change() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+    set_timelock(deps.as_mut(), env.clone(), 10);
+    raise_protocol_fee(deps.as_mut(), env.clone());
+
+    let info = mock_info("not_creator", &[]);
+    let res = execute::cancel_config_change(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        CancelConfigChangeMsg { id: 0 },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    execute::cancel_config_change(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        CancelConfigChangeMsg { id: 0 },
+    )
+    .unwrap();
+
+    env.block.height += 10;
+    let res =
+        execute::apply_config_change(deps.as_mut(), env, info, ApplyConfigChangeMsg { id: 0 });
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NoMatchingConfigChange { change_id: 0 }
+    );
+}
+
+#[test]
+fn shortening_timelock_is_timelocked() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+    set_timelock(deps.as_mut(), env.clone(), 10);
+
+    set_timelock(deps.as_mut(), env.clone(), 0);
+    raise_protocol_fee(deps.as_mut(), env.clone());
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.protocol_fee, 100);
+
+    env.block.height += 10;
+    let info = mock_info("anyone", &[]);
+    execute::apply_config_change(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ApplyConfigChangeMsg { id: 0 },
+    )
+    .unwrap();
+
+    // The timelock is now zero, so further changes apply immediately.
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        protocol_fee: Some(200),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env, info, msg).unwrap();
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.protocol_fee, 200);
+}
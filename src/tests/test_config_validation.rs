@@ -0,0 +1,179 @@
+use cosmwasm_std::{
+    testing::{mock_dependencies, mock_env, mock_info},
+    Decimal, Uint128,
+};
+use entropy_beacon_cosmos::{beacon::UpdateConfigMsg, msg::InstantiateMsg};
+
+use crate::{
+    contract::instantiate,
+    execute,
+    msg::SetFeeDenomMsg,
+    query,
+    state::DenomPricing,
+    tests::{default_instantiate, funded_dependencies, test_submit_entropy::request_entropy},
+    utils::empty_config_update,
+    ContractError,
+};
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        whitelist_deposit_amt: Uint128::from(1000u128),
+        refund_increment_amt: Uint128::from(1000u128),
+        key_activation_delay: 1,
+        protocol_fee: 100,
+        submitter_share: 80,
+        native_denom: "uluna".to_string(),
+        whitelisted_keys: vec![],
+        belief_gas_price: Decimal::percent(15),
+        permissioned: false,
+        test_mode: false,
+        subsidize_callbacks: false,
+    }
+}
+
+#[test]
+fn rejects_invalid_instantiation() {
+    let cases = [
+        (
+            InstantiateMsg {
+                submitter_share: 101,
+                ..instantiate_msg()
+            },
+            ContractError::InvalidSubmitterShare {},
+        ),
+        (
+            InstantiateMsg {
+                native_denom: "".to_string(),
+                ..instantiate_msg()
+            },
+            ContractError::EmptyNativeDenom {},
+        ),
+        (
+            InstantiateMsg {
+                refund_increment_amt: Uint128::from(1001u128),
+                ..instantiate_msg()
+            },
+            ContractError::RefundIncrementExceedsDeposit {},
+        ),
+    ];
+
+    for (msg, err) in cases {
+        let mut deps = mock_dependencies();
+        let info = mock_info("creator", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg);
+        assert_eq!(res.unwrap_err(), err);
+    }
+}
+
+#[test]
+fn rejects_invalid_update() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        whitelist_deposit_amt: Some(Uint128::from(500u128)),
+        ..empty_config_update()
+    };
+    let res = execute::update_config(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::RefundIncrementExceedsDeposit {}
+    );
+
+    let msg = UpdateConfigMsg {
+        submitter_share: Some(150),
+        ..empty_config_update()
+    };
+    let res = execute::update_config(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidSubmitterShare {});
+
+    let cfg = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(cfg.whitelist_deposit_amt, Uint128::from(1000u128));
+    assert_eq!(cfg.submitter_share, Decimal::percent(80));
+}
+
+#[test]
+fn dry_runs_updates() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = UpdateConfigMsg {
+        protocol_fee: Some(200),
+        ..empty_config_update()
+    };
+    let res = query::validate_config_update_query(deps.as_ref(), msg).unwrap();
+    assert!(res.valid);
+    assert!(res.errors.is_empty());
+
+    let msg = UpdateConfigMsg {
+        submitter_share: Some(101),
+        native_denom: Some("".to_string()),
+        ..empty_config_update()
+    };
+    let res = query::validate_config_update_query(deps.as_ref(), msg).unwrap();
+    assert!(!res.valid);
+    assert_eq!(
+        res.errors,
+        vec![
+            ContractError::InvalidSubmitterShare {}.to_string(),
+            ContractError::EmptyNativeDenom {}.to_string(),
+        ]
+    );
+}
+
+#[test]
+fn rejects_native_denom_changes_that_strand_funds() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("creator", &[]);
+    let msg = SetFeeDenomMsg {
+        denom: "uatom".to_string(),
+        pricing: Some(DenomPricing {
+            protocol_fee: 10,
+            gas_price: Decimal::percent(2),
+            deposit: None,
+        }),
+    };
+    execute::set_fee_denom(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = UpdateConfigMsg {
+        native_denom: Some("uatom".to_string()),
+        ..empty_config_update()
+    };
+    let res = execute::update_config(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NativeDenomIsFeeDenom {
+            denom: "uatom".to_string()
+        }
+    );
+
+    // Switching is fine while nothing is held in the old denom.
+    let msg = UpdateConfigMsg {
+        native_denom: Some("uosmo".to_string()),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = UpdateConfigMsg {
+        native_denom: Some("uluna".to_string()),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    request_entropy(&mut deps, &mut env);
+    let msg = UpdateConfigMsg {
+        native_denom: Some("uosmo".to_string()),
+        ..empty_config_update()
+    };
+    let res = query::validate_config_update_query(deps.as_ref(), msg.clone()).unwrap();
+    assert!(!res.valid);
+    let res = execute::update_config(deps.as_mut(), env, info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::NativeDenomInUse {
+            denom: "uluna".to_string()
+        }
+    );
+}
@@ -0,0 +1,138 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_env, mock_info},
+    BankMsg, Coin, CosmosMsg, Deps, Uint128,
+};
+
+use crate::{
+    execute,
+    msg::{CreditQuery, DepositCreditMsg, RequestEntropyMsg, SolvencyQuery, WithdrawCreditMsg},
+    query,
+    state::ENTROPY_REQUESTS,
+    ContractError,
+};
+
+use super::{default_instantiate, funded_dependencies, request_msg};
+
+fn credit(deps: Deps, address: &str) -> Vec<Coin> {
+    let data = CreditQuery {
+        address: address.to_string(),
+    };
+    query::credit_query(deps, data).unwrap().credits
+}
+
+fn credit_request_msg() -> RequestEntropyMsg {
+    RequestEntropyMsg {
+        pay_from_credit: Some("uluna".to_string()),
+        ..request_msg()
+    }
+}
+
+#[test]
+fn deposits_and_withdraws() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = DepositCreditMsg { address: None };
+    let info = mock_info("game", &[]);
+    let res = execute::deposit_credit(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    let info = mock_info("game", &coins(1000, "uluna"));
+    execute::deposit_credit(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(credit(deps.as_ref(), "game"), coins(1000, "uluna"));
+
+    let res =
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.credits, Uint128::from(1000u128));
+
+    let msg = WithdrawCreditMsg {
+        amount: Some(Uint128::from(1001u128)),
+        recipient: None,
+        denom: None,
+    };
+    let info = mock_info("game", &[]);
+    let res = execute::withdraw_credit(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientCredit {
+            available: Uint128::from(1000u128)
+        }
+    );
+
+    let msg = WithdrawCreditMsg {
+        amount: Some(Uint128::from(300u128)),
+        recipient: Some("cold_wallet".to_string()),
+        denom: None,
+    };
+    let res = execute::withdraw_credit(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "cold_wallet".to_string(),
+            amount: coins(300, "uluna"),
+        })
+    );
+    assert_eq!(credit(deps.as_ref(), "game"), coins(700, "uluna"));
+
+    let msg = WithdrawCreditMsg {
+        amount: None,
+        recipient: None,
+        denom: None,
+    };
+    let res = execute::withdraw_credit(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "game".to_string(),
+            amount: coins(700, "uluna"),
+        })
+    );
+    assert!(credit(deps.as_ref(), "game").is_empty());
+}
+
+#[test]
+fn pays_requests_from_credit() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    // Anyone can top up the credit of another address.
+    let msg = DepositCreditMsg {
+        address: Some("game".to_string()),
+    };
+    let info = mock_info("funder", &coins(600, "uluna"));
+    execute::deposit_credit(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(credit(deps.as_ref(), "funder").is_empty());
+
+    let info = mock_info("game", &coins(250, "uluna"));
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, credit_request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::FundsWithCredit {});
+
+    let info = mock_info("game", &[]);
+    for id in 0..2u128 {
+        let res = execute::request_entropy(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            credit_request_msg(),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let request = ENTROPY_REQUESTS.load(&deps.storage, id).unwrap();
+        assert_eq!(request.submitted_bounty_amount, Uint128::from(250u128));
+    }
+    assert_eq!(credit(deps.as_ref(), "game"), vec![coin(100, "uluna")]);
+
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, credit_request_msg());
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientCredit {
+            available: Uint128::from(100u128)
+        }
+    );
+
+    let res =
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.credits, Uint128::from(100u128));
+    assert_eq!(res.bounties, Uint128::from(500u128));
+}
@@ -0,0 +1,259 @@
+use cosmwasm_std::{to_binary, Addr, Decimal, Empty, Uint128};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::{
+    msg::InstantiateMsg,
+    provide::{ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg},
+};
+
+use crate::{
+    contract,
+    msg::{
+        ClaimRewardsMsg, ExecuteMsg, QueryMsg, ReceiveMsg, RequestEntropyMsg, SetFeeDenomMsg,
+        SolvencyQuery, SolvencyResponse, WithdrawRevenueMsg,
+    },
+    state::{DenomDeposit, DenomPricing},
+    ContractError,
+};
+
+use super::{request_msg, test_pk, test_sk};
+
+fn beacon_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(contract::execute, contract::instantiate, contract::query)
+        .with_reply(contract::reply);
+    Box::new(contract)
+}
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn instantiate_token(app: &mut App, code_id: u64, symbol: &str) -> Addr {
+    let msg = cw20_base::msg::InstantiateMsg {
+        name: "Stable Token".to_string(),
+        symbol: symbol.to_string(),
+        decimals: 6,
+        initial_balances: vec![
+            Cw20Coin {
+                address: "holder".to_string(),
+                amount: Uint128::from(10_000u128),
+            },
+            Cw20Coin {
+                address: "requester".to_string(),
+                amount: Uint128::from(10_000u128),
+            },
+        ],
+        mint: None,
+        marketing: None,
+    };
+    app.instantiate_contract(code_id, Addr::unchecked("creator"), &msg, &[], symbol, None)
+        .unwrap()
+}
+
+/// Sets up a beacon that accepts the returned token for fees and deposits.
+fn setup() -> (App, Addr, Addr) {
+    let mut app = App::default();
+    let cw20_id = app.store_code(cw20_contract());
+    let beacon_id = app.store_code(beacon_contract());
+    let token = instantiate_token(&mut app, cw20_id, "USDC");
+
+    let msg = InstantiateMsg {
+        whitelist_deposit_amt: Uint128::from(1000u128),
+        refund_increment_amt: Uint128::from(1000u128),
+        key_activation_delay: 1,
+        protocol_fee: 100,
+        submitter_share: 80,
+        native_denom: "uluna".to_string(),
+        whitelisted_keys: vec![],
+        belief_gas_price: Decimal::percent(15),
+        permissioned: false,
+        test_mode: false,
+        subsidize_callbacks: false,
+    };
+    let beacon = app
+        .instantiate_contract(
+            beacon_id,
+            Addr::unchecked("creator"),
+            &msg,
+            &[],
+            "beacon",
+            None,
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::SetFeeDenom(SetFeeDenomMsg {
+        denom: format!("cw20:{}", token),
+        pricing: Some(DenomPricing {
+            protocol_fee: 10,
+            gas_price: Decimal::percent(2),
+            deposit: Some(DenomDeposit {
+                whitelist_deposit_amt: Uint128::from(500u128),
+                refund_increment_amt: Uint128::from(100u128),
+            }),
+        }),
+    });
+    app.execute_contract(Addr::unchecked("creator"), beacon.clone(), &msg, &[])
+        .unwrap();
+
+    (app, beacon, token)
+}
+
+fn send(
+    app: &mut App,
+    sender: &str,
+    token: &Addr,
+    beacon: &Addr,
+    amount: u128,
+    msg: &ReceiveMsg,
+) -> anyhow::Result<AppResponse> {
+    let msg = Cw20ExecuteMsg::Send {
+        contract: beacon.to_string(),
+        amount: Uint128::from(amount),
+        msg: to_binary(msg).unwrap(),
+    };
+    app.execute_contract(Addr::unchecked(sender), token.clone(), &msg, &[])
+}
+
+fn token_balance(app: &App, token: &Addr, address: &str) -> u128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance.u128()
+}
+
+fn solvency(app: &App, beacon: &Addr, token: &Addr) -> SolvencyResponse {
+    let msg = QueryMsg::Solvency(SolvencyQuery {
+        denom: Some(format!("cw20:{}", token)),
+    });
+    app.wrap().query_wasm_smart(beacon, &msg).unwrap()
+}
+
+fn hook_msg(max_fee: u128) -> ReceiveMsg {
+    ReceiveMsg::RequestEntropy(RequestEntropyMsg {
+        max_fee: Some(Uint128::from(max_fee)),
+        ..request_msg()
+    })
+}
+
+#[test]
+fn pays_and_is_paid_in_cw20() {
+    let (mut app, beacon, token) = setup();
+    let denom = format!("cw20:{}", token);
+
+    let msg = ReceiveMsg::WhitelistPublicKey(WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    });
+    let err = send(&mut app, "holder", &token, &beacon, 499, &msg).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientFunds {}
+    );
+    // Anything sent beyond the deposit is refunded.
+    send(&mut app, "holder", &token, &beacon, 600, &msg).unwrap();
+    assert_eq!(token_balance(&app, &token, "holder"), 9_500);
+
+    // A 1000 gas callback costs 1000 * 0.02 + 10, and the rest is refunded.
+    send(&mut app, "requester", &token, &beacon, 40, &hook_msg(35)).unwrap();
+    assert_eq!(token_balance(&app, &token, "requester"), 9_970);
+
+    let res = solvency(&app, &beacon, &token);
+    assert_eq!(res.balance, Uint128::from(530u128));
+    assert_eq!(res.deposits, Uint128::from(500u128));
+    assert_eq!(res.bounties, Uint128::from(30u128));
+    assert!(res.solvent);
+
+    app.update_block(|block| block.height += 1);
+    let msg = ExecuteMsg::SubmitEntropy(SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    });
+    app.execute_contract(Addr::unchecked("holder"), beacon.clone(), &msg, &[])
+        .unwrap();
+
+    // The holder is owed 80% of the bounty, and one refund increment of the deposit.
+    let msg = ExecuteMsg::ClaimRewards(ClaimRewardsMsg {
+        amount: None,
+        address: None,
+        denom: Some(denom.clone()),
+    });
+    app.execute_contract(Addr::unchecked("holder"), beacon.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(token_balance(&app, &token, "holder"), 9_524);
+
+    let msg = ExecuteMsg::ReclaimDeposit(ReclaimDepositMsg {
+        public_key: test_pk(),
+    });
+    app.execute_contract(Addr::unchecked("holder"), beacon.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(token_balance(&app, &token, "holder"), 9_624);
+
+    // The protocol keeps its share of the bounty and the forfeited deposit.
+    let res = solvency(&app, &beacon, &token);
+    assert_eq!(res.revenue, Uint128::from(406u128));
+    assert_eq!(res.liabilities, res.balance);
+
+    let msg = ExecuteMsg::WithdrawRevenue(WithdrawRevenueMsg {
+        amount: None,
+        recipient: None,
+        denom: Some(denom),
+    });
+    app.execute_contract(Addr::unchecked("creator"), beacon.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(token_balance(&app, &token, "creator"), 406);
+    assert_eq!(token_balance(&app, &token, beacon.as_str()), 0);
+}
+
+#[test]
+fn rejects_unaccepted_tokens() {
+    let (mut app, beacon, _) = setup();
+    let cw20_id = app.store_code(cw20_contract());
+    let other = instantiate_token(&mut app, cw20_id, "OTHER");
+
+    let err = send(&mut app, "requester", &other, &beacon, 40, &hook_msg(35)).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnsupportedDenom {
+            denom: format!("cw20:{}", other)
+        }
+    );
+    assert_eq!(token_balance(&app, &other, "requester"), 10_000);
+}
+
+#[test]
+fn rejects_deposits_in_fee_only_tokens() {
+    let (mut app, beacon, token) = setup();
+    let msg = ExecuteMsg::SetFeeDenom(SetFeeDenomMsg {
+        denom: format!("cw20:{}", token),
+        pricing: Some(DenomPricing {
+            protocol_fee: 10,
+            gas_price: Decimal::percent(2),
+            deposit: None,
+        }),
+    });
+    app.execute_contract(Addr::unchecked("creator"), beacon.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = ReceiveMsg::WhitelistPublicKey(WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    });
+    let err = send(&mut app, "holder", &token, &beacon, 500, &msg).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnsupportedDenom {
+            denom: format!("cw20:{}", token)
+        }
+    );
+
+    send(&mut app, "requester", &token, &beacon, 30, &hook_msg(30)).unwrap();
+}
@@ -0,0 +1,84 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    Decimal, Empty, Env, OwnedDeps, Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::{msg::InstantiateMsg, provide::SubmitEntropyMsg};
+
+use crate::{contract::instantiate, execute, msg::ExitTestModeMsg, query, ContractError};
+
+use super::{funded_dependencies, request_msg, test_sk};
+
+fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>, env: &Env) {
+    let msg = InstantiateMsg {
+        whitelist_deposit_amt: Uint128::from(1000u128),
+        refund_increment_amt: Uint128::from(1000u128),
+        key_activation_delay: 1,
+        protocol_fee: 100,
+        submitter_share: 80,
+        native_denom: "uluna".to_string(),
+        whitelisted_keys: vec![],
+        belief_gas_price: Decimal::percent(15),
+        permissioned: false,
+        test_mode: true,
+        subsidize_callbacks: false,
+    };
+    let info = mock_info("creator", &[]);
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("requester", &[coin(1100, "uluna")]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+}
+
+fn submit(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    env: &Env,
+) -> Result<(), ContractError> {
+    let info = mock_info("anyone", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "test").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info, msg).map(|_| ())
+}
+
+#[test]
+fn exits_once_requests_are_fulfilled() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    setup_contract(&mut deps, &env);
+
+    let info = mock_info("creator", &[]);
+    let res = execute::exit_test_mode(deps.as_mut(), env.clone(), info.clone(), ExitTestModeMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::PendingTestModeRequests {});
+
+    submit(&mut deps, &env).unwrap();
+
+    let res = execute::exit_test_mode(deps.as_mut(), env.clone(), info.clone(), ExitTestModeMsg {})
+        .unwrap();
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "test_mode_exited");
+    assert!(!query::beacon_config_query(deps.as_ref()).unwrap().test_mode);
+
+    // Keys and proofs are now checked.
+    assert_eq!(
+        submit(&mut deps, &env).unwrap_err(),
+        ContractError::InvalidMessage {}
+    );
+
+    let res = execute::exit_test_mode(deps.as_mut(), env, info, ExitTestModeMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::NotInTestMode {});
+}
+
+#[test]
+fn rejects_unauthorized() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    setup_contract(&mut deps, &env);
+    submit(&mut deps, &env).unwrap();
+
+    let info = mock_info("not_creator", &[]);
+    let res = execute::exit_test_mode(deps.as_mut(), env, info, ExitTestModeMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
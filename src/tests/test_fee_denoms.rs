@@ -0,0 +1,160 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_dependencies_with_balance, mock_env, mock_info},
+    BankMsg, CosmosMsg, Decimal, Deps, DepsMut, StdResult, Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::SubmitEntropyMsg;
+
+use crate::{
+    execute,
+    msg::{
+        CalculateFeeQuery, ClaimRewardsMsg, FeeDenom, SetFeeDenomMsg, SolvencyQuery,
+        UnclaimedRewardsQuery,
+    },
+    query,
+    state::{DenomPricing, ENTROPY_REQUESTS},
+    ContractError,
+};
+
+use super::{
+    default_instantiate, funded_dependencies, request_msg, test_sk,
+    test_submit_entropy::setup_contract,
+};
+
+fn usdc_pricing() -> DenomPricing {
+    DenomPricing {
+        protocol_fee: 10,
+        gas_price: Decimal::percent(2),
+        deposit: None,
+    }
+}
+
+fn add_usdc(deps: DepsMut) {
+    let msg = SetFeeDenomMsg {
+        denom: "uusdc".to_string(),
+        pricing: Some(usdc_pricing()),
+    };
+    let info = mock_info("creator", &[]);
+    execute::set_fee_denom(deps, mock_env(), info, msg).unwrap();
+}
+
+fn fee(deps: Deps, denom: Option<&str>) -> StdResult<u64> {
+    let data = CalculateFeeQuery {
+        callback_gas_limit: 1000,
+        denom: denom.map(str::to_string),
+    };
+    query::calculate_fee_query(deps, data).map(|res| res.fee)
+}
+
+#[test]
+fn only_owner_sets_fee_denoms() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = SetFeeDenomMsg {
+        denom: "uusdc".to_string(),
+        pricing: Some(usdc_pricing()),
+    };
+    let info = mock_info("not_creator", &[]);
+    let res = execute::set_fee_denom(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let msg = SetFeeDenomMsg {
+        denom: "uluna".to_string(),
+        pricing: Some(usdc_pricing()),
+    };
+    let info = mock_info("creator", &[]);
+    let res = execute::set_fee_denom(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidFeeDenom {});
+
+    add_usdc(deps.as_mut());
+    let res = query::fee_denoms_query(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.denoms,
+        vec![FeeDenom {
+            denom: "uusdc".to_string(),
+            pricing: usdc_pricing(),
+        }]
+    );
+
+    let msg = SetFeeDenomMsg {
+        denom: "uusdc".to_string(),
+        pricing: None,
+    };
+    let info = mock_info("creator", &[]);
+    execute::set_fee_denom(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(query::fee_denoms_query(deps.as_ref())
+        .unwrap()
+        .denoms
+        .is_empty());
+}
+
+#[test]
+fn calculates_fee_per_denom() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+    add_usdc(deps.as_mut());
+
+    assert_eq!(fee(deps.as_ref(), None).unwrap(), 250); // 1000 * 0.15 + 100
+    assert_eq!(fee(deps.as_ref(), Some("uluna")).unwrap(), 250);
+    assert_eq!(fee(deps.as_ref(), Some("uusdc")).unwrap(), 30); // 1000 * 0.02 + 10
+    assert!(fee(deps.as_ref(), Some("uatom")).is_err());
+}
+
+#[test]
+fn pays_out_in_request_denom() {
+    let mut deps =
+        mock_dependencies_with_balance(&[coin(1_000_000_000, "uluna"), coin(30, "uusdc")]);
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+    add_usdc(deps.as_mut());
+
+    let info = mock_info("requester", &[coin(29, "uusdc")]);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    let info = mock_info("requester", &[coin(30, "uusdc")]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+    let request = ENTROPY_REQUESTS.load(&deps.storage, 1).unwrap();
+    assert_eq!(request.denom, "uusdc");
+    assert_eq!(request.submitted_bounty_amount, Uint128::from(30u128));
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // 80% of the 1100 uluna bounty and of the 30 uusdc bounty.
+    let data = UnclaimedRewardsQuery {
+        address: "submitter".to_string(),
+    };
+    let rewards = query::unclaimed_rewards_query(deps.as_ref(), data)
+        .unwrap()
+        .rewards;
+    assert_eq!(rewards, vec![coin(880, "uluna"), coin(24, "uusdc")]);
+
+    let data = SolvencyQuery {
+        denom: Some("uusdc".to_string()),
+    };
+    let res = query::solvency_query(deps.as_ref(), env.clone(), data).unwrap();
+    assert_eq!(res.rewards, Uint128::from(24u128));
+    assert_eq!(res.revenue, Uint128::from(6u128));
+    assert_eq!(res.bounties, Uint128::zero());
+
+    let msg = ClaimRewardsMsg {
+        amount: None,
+        address: None,
+        denom: Some("uusdc".to_string()),
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "submitter".to_string(),
+            amount: vec![coin(24, "uusdc")],
+        })
+    );
+}
@@ -0,0 +1,244 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_env, mock_info},
+    Addr, BankMsg, CosmosMsg, Deps, Env, Uint128,
+};
+
+use crate::{
+    execute,
+    msg::{
+        FeeGrantQuery, FeeGrantsQuery, GrantFeeMsg, ProcessSunsetMsg, RevokeFeeGrantMsg,
+        SolvencyQuery, StartSunsetMsg,
+    },
+    query,
+    state::ENTROPY_REQUESTS,
+    ContractError,
+};
+
+use super::{default_instantiate, funded_dependencies, request_msg};
+
+fn grant_msg(grantee: &str) -> GrantFeeMsg {
+    GrantFeeMsg {
+        grantee: grantee.to_string(),
+        callback_requesters: None,
+        spend_limit: None,
+        per_request_limit: None,
+        expires_at: None,
+    }
+}
+
+fn allowance(deps: Deps, env: &Env, grantee: &str) -> Option<Uint128> {
+    let data = FeeGrantQuery {
+        sponsor: "sponsor".to_string(),
+        grantee: grantee.to_string(),
+        denom: None,
+    };
+    let res = query::fee_grant_query(deps, env.clone(), data).unwrap();
+    res.grant.map(|info| info.allowance)
+}
+
+#[test]
+fn pays_requests_within_limits() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let msg = GrantFeeMsg {
+        spend_limit: Some(Uint128::from(500u128)),
+        per_request_limit: Some(Uint128::from(300u128)),
+        ..grant_msg("game")
+    };
+    let info = mock_info("sponsor", &coins(600, "uluna"));
+    execute::grant_fee(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        allowance(deps.as_ref(), &env, "game"),
+        Some(Uint128::from(300u128))
+    );
+
+    let info = mock_info("game", &[]);
+    for id in 0..2u128 {
+        let res = execute::request_entropy(deps.as_mut(), env.clone(), info.clone(), request_msg())
+            .unwrap();
+        assert!(res.messages.is_empty());
+        let request = ENTROPY_REQUESTS.load(&deps.storage, id).unwrap();
+        assert_eq!(request.submitted_bounty_amount, Uint128::from(250u128));
+    }
+
+    // The spend limit has been reached, although funds are left in the grant.
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    let data = FeeGrantsQuery {
+        grantee: "game".to_string(),
+    };
+    let res = query::fee_grants_query(deps.as_ref(), env.clone(), data).unwrap();
+    assert_eq!(res.grants.len(), 1);
+    assert_eq!(res.grants[0].grant.balance, Uint128::from(100u128));
+    assert_eq!(res.grants[0].grant.spent, Uint128::from(500u128));
+    assert_eq!(res.grants[0].allowance, Uint128::zero());
+
+    let res = query::solvency_query(deps.as_ref(), env, SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.grants, Uint128::from(100u128));
+    assert_eq!(res.bounties, Uint128::from(500u128));
+}
+
+#[test]
+fn only_grantee_draws_until_expiry() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let msg = GrantFeeMsg {
+        expires_at: Some(env.block.height),
+        ..grant_msg("callback_address")
+    };
+    let info = mock_info("sponsor", &coins(1000, "uluna"));
+    let res = execute::grant_fee(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidFeeGrantExpiry {});
+
+    let msg = GrantFeeMsg {
+        expires_at: Some(env.block.height + 1),
+        ..grant_msg("callback_address")
+    };
+    execute::grant_fee(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Naming the grantee as the callback address does not draw on its grant.
+    let info = mock_info("anyone", &[]);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    // Funds sent along with a request are used instead of the grant.
+    let info = mock_info("callback_address", &coins(250, "uluna"));
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+    assert_eq!(
+        allowance(deps.as_ref(), &env, "callback_address"),
+        Some(Uint128::from(1000u128))
+    );
+
+    let info = mock_info("callback_address", &[]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info.clone(), request_msg()).unwrap();
+    assert_eq!(
+        allowance(deps.as_ref(), &env, "callback_address"),
+        Some(Uint128::from(750u128))
+    );
+
+    env.block.height += 1;
+    assert_eq!(
+        allowance(deps.as_ref(), &env, "callback_address"),
+        Some(Uint128::zero())
+    );
+    let res = execute::request_entropy(deps.as_mut(), env, info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn pays_callbacks_for_allowed_requesters() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let msg = GrantFeeMsg {
+        callback_requesters: Some(vec!["player".to_string()]),
+        ..grant_msg("callback_address")
+    };
+    let info = mock_info("sponsor", &coins(1000, "uluna"));
+    execute::grant_fee(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("player", &[]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+    assert_eq!(
+        allowance(deps.as_ref(), &env, "callback_address"),
+        Some(Uint128::from(750u128))
+    );
+    let request = ENTROPY_REQUESTS.load(&deps.storage, 0).unwrap();
+    assert_eq!(
+        request.fee_grant.map(|key| key.grantee),
+        Some(Addr::unchecked("callback_address"))
+    );
+
+    let info = mock_info("anyone", &[]);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn revokes_unspent_funds() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("sponsor", &coins(400, "uluna"));
+    execute::grant_fee(deps.as_mut(), env.clone(), info, grant_msg("game")).unwrap();
+    let info = mock_info("game", &[]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+
+    let msg = RevokeFeeGrantMsg {
+        grantee: "game".to_string(),
+        denom: None,
+    };
+    let info = mock_info("not_sponsor", &[]);
+    let res = execute::revoke_fee_grant(deps.as_mut(), env.clone(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::FeeGrantNotFound {});
+
+    let info = mock_info("sponsor", &[]);
+    let res = execute::revoke_fee_grant(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "sponsor".to_string(),
+            amount: coins(150, "uluna"),
+        })
+    );
+    assert_eq!(allowance(deps.as_ref(), &env, "game"), None);
+
+    let res = query::solvency_query(deps.as_ref(), env, SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.grants, Uint128::zero());
+}
+
+#[test]
+fn refunds_bounties_to_grants() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("sponsor", &coins(400, "uluna"));
+    execute::grant_fee(deps.as_mut(), env.clone(), info, grant_msg("game")).unwrap();
+    let info = mock_info("other_sponsor", &coins(400, "uluna"));
+    execute::grant_fee(deps.as_mut(), env.clone(), info, grant_msg("app")).unwrap();
+    for requester in ["game", "app"] {
+        let info = mock_info(requester, &[]);
+        execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+    }
+
+    // A revoked grant can no longer be refunded to, so its sponsor is.
+    let msg = RevokeFeeGrantMsg {
+        grantee: "app".to_string(),
+        denom: None,
+    };
+    let info = mock_info("other_sponsor", &[]);
+    execute::revoke_fee_grant(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    execute::start_sunset(deps.as_mut(), env.clone(), info.clone(), StartSunsetMsg {}).unwrap();
+    let msg = ProcessSunsetMsg { limit: None };
+    let res = execute::process_sunset(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "other_sponsor".to_string(),
+            amount: coins(250, "uluna"),
+        })
+    );
+
+    let data = FeeGrantsQuery {
+        grantee: "game".to_string(),
+    };
+    let res = query::fee_grants_query(deps.as_ref(), env.clone(), data).unwrap();
+    assert_eq!(res.grants[0].grant.balance, Uint128::from(400u128));
+    assert_eq!(res.grants[0].grant.spent, Uint128::zero());
+
+    let res = query::solvency_query(deps.as_ref(), env, SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.grants, Uint128::from(400u128));
+    assert_eq!(res.bounties, Uint128::zero());
+}
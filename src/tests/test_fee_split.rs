@@ -0,0 +1,173 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_env, mock_info},
+    Addr, BankMsg, CosmosMsg, Decimal,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::SubmitEntropyMsg;
+
+use crate::{
+    execute,
+    msg::{RequestEntropyMsg, SetFeeSplitMsg},
+    query,
+    state::{FeeRecipient, FeeShare},
+    ContractError,
+};
+
+use super::{funded_dependencies, request_msg, test_sk, test_submit_entropy::setup_contract};
+
+fn share(recipient: FeeRecipient, percent: u64) -> FeeShare {
+    FeeShare {
+        recipient,
+        share: Decimal::percent(percent),
+    }
+}
+
+#[test]
+fn defaults_to_submitter_share() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let res = query::fee_split_query(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.shares,
+        vec![
+            share(FeeRecipient::Submitter, 80),
+            share(FeeRecipient::Protocol, 20)
+        ]
+    );
+}
+
+#[test]
+fn rejects_invalid_splits() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let msg = SetFeeSplitMsg {
+        shares: Some(vec![share(FeeRecipient::Submitter, 100)]),
+    };
+    let info = mock_info("not_creator", &[]);
+    let res = execute::set_fee_split(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    for shares in [
+        vec![share(FeeRecipient::Submitter, 60)],
+        vec![
+            share(FeeRecipient::Submitter, 60),
+            share(FeeRecipient::Burn, 50),
+        ],
+        vec![],
+    ] {
+        let msg = SetFeeSplitMsg {
+            shares: Some(shares),
+        };
+        let res = execute::set_fee_split(deps.as_mut(), env.clone(), info.clone(), msg);
+        assert_eq!(res.unwrap_err(), ContractError::InvalidFeeSplit {});
+    }
+}
+
+#[test]
+fn splits_each_bounty() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    // Only the second request was referred.
+    let info = mock_info("requester", &coins(1100, "uluna"));
+    let msg = RequestEntropyMsg {
+        bounty: Some(1100u128.into()),
+        referrer: Some("referrer".to_string()),
+        ..request_msg()
+    };
+    execute::request_entropy(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    let msg = SetFeeSplitMsg {
+        shares: Some(vec![
+            share(FeeRecipient::Submitter, 50),
+            share(
+                FeeRecipient::Treasury {
+                    address: Addr::unchecked("treasury"),
+                },
+                25,
+            ),
+            share(FeeRecipient::Burn, 10),
+            share(FeeRecipient::Referrer, 5),
+            share(FeeRecipient::Protocol, 10),
+        ]),
+    };
+    execute::set_fee_split(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let res = query::beacon_config_query(deps.as_ref()).unwrap();
+    assert_eq!(res.submitter_share, Decimal::percent(50));
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    let res = execute::submit_entropy(deps.as_mut(), env, info, msg).unwrap();
+
+    let send = |to: &str, amount: u128| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount, "uluna"),
+        })
+    };
+    let msgs: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+    assert_eq!(msgs[0], send("treasury", 550));
+    assert_eq!(
+        msgs[1],
+        CosmosMsg::Bank(BankMsg::Burn {
+            amount: coins(220, "uluna")
+        })
+    );
+    assert_eq!(msgs[2], send("referrer", 55));
+
+    // The referrer share of the first request is kept as revenue.
+    assert_eq!(res.events.len(), 2);
+    for (id, event) in res.events.iter().enumerate() {
+        let (referrer, protocol) = if id == 0 { ("0", "165") } else { ("55", "110") };
+        assert_eq!(event.ty, "fee_split");
+        let attrs: Vec<_> = event
+            .attributes
+            .iter()
+            .map(|a| (a.key.as_str(), a.value.as_str()))
+            .collect();
+        assert_eq!(
+            attrs,
+            vec![
+                ("request_id", id.to_string().as_str()),
+                ("denom", "uluna"),
+                ("submitter", "550"),
+                ("treasury", "275"),
+                ("burn", "110"),
+                ("referrer", referrer),
+                ("protocol", protocol),
+            ]
+        );
+    }
+
+    let res = query::protocol_revenue_query(deps.as_ref()).unwrap();
+    assert_eq!(res.revenue, coins(275, "uluna"));
+}
+
+#[test]
+fn rejects_self_referral() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    for referrer in ["requester", "callback_address"] {
+        let info = mock_info("requester", &coins(1100, "uluna"));
+        let msg = RequestEntropyMsg {
+            referrer: Some(referrer.to_string()),
+            ..request_msg()
+        };
+        let res = execute::request_entropy(deps.as_mut(), env.clone(), info, msg);
+        assert_eq!(res.unwrap_err(), ContractError::InvalidReferrer {});
+    }
+}
@@ -0,0 +1,137 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg, Uint128,
+};
+use entropy_beacon_cosmos::provide::WhitelistPublicKeyMsg;
+
+use crate::{
+    contract, execute,
+    msg::{ClaimRewardsMsg, ExecuteMsg, SetForeignDenomPolicyMsg, SolvencyQuery},
+    query,
+    state::{ForeignDenomPolicy, ENTROPY_REQUESTS},
+    ContractError,
+};
+
+use super::{default_instantiate, funded_dependencies, request_msg, test_pk};
+
+#[test]
+fn rejects_mixed_funds_by_default() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+    let funds = [coin(1100, "uluna"), coin(5, "uatom")];
+
+    let info = mock_info("requester", &funds);
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg());
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedDenom {
+            denom: "uatom".to_string()
+        }
+    );
+
+    let info = mock_info("key_holder", &funds);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::whitelist_key(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedDenom {
+            denom: "uatom".to_string()
+        }
+    );
+
+    // Zero amounts of other denoms are ignored.
+    let info = mock_info("requester", &[coin(1100, "uluna"), coin(0, "uatom")]);
+    execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg()).unwrap();
+}
+
+#[test]
+fn refunds_mixed_funds() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = SetForeignDenomPolicyMsg {
+        policy: ForeignDenomPolicy::Refund,
+    };
+    let info = mock_info("not_creator", &[]);
+    let res = execute::set_foreign_denom_policy(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    execute::set_foreign_denom_policy(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let foreign = vec![coin(5, "uatom"), coin(7, "uosmo")];
+    let refund = |to: &str| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: foreign.clone(),
+        })
+    };
+
+    let mut funds = foreign.clone();
+    funds.push(coin(250, "uluna"));
+    let info = mock_info("requester", &funds);
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].msg, refund("requester"));
+    let request = ENTROPY_REQUESTS.load(deps.as_ref().storage, 0).unwrap();
+    assert_eq!(request.submitted_bounty_amount, Uint128::from(250u128));
+
+    let mut funds = foreign.clone();
+    funds.push(coin(1000, "uluna"));
+    let info = mock_info("key_holder", &funds);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::whitelist_key(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("key_holder"));
+
+    // Only native coins are counted towards what is owed.
+    let res =
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.liabilities, Uint128::from(1250u128));
+
+    let info = mock_info("requester", &coins(5, "uatom"));
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn never_keeps_funds_sent_to_nonpayable_messages() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+    let funds = [coin(100, "uluna"), coin(5, "uatom")];
+    let claim = || {
+        ExecuteMsg::ClaimRewards(ClaimRewardsMsg {
+            amount: None,
+            address: None,
+            denom: None,
+        })
+    };
+
+    let info = mock_info("claimer", &funds);
+    let res = contract::execute(deps.as_mut(), mock_env(), info, claim());
+    assert_eq!(res.unwrap_err(), ContractError::NonPayable {});
+
+    let info = mock_info("creator", &[]);
+    let msg = SetForeignDenomPolicyMsg {
+        policy: ForeignDenomPolicy::Refund,
+    };
+    execute::set_foreign_denom_policy(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // Every denom is sent back, including the native one.
+    let info = mock_info(
+        "claimer",
+        &[funds[0].clone(), funds[1].clone(), coin(0, "uosmo")],
+    );
+    let res = contract::execute(deps.as_mut(), mock_env(), info, claim()).unwrap();
+    assert_eq!(
+        res.messages.last().unwrap().msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "claimer".to_string(),
+            amount: funds.to_vec(),
+        })
+    );
+}
@@ -0,0 +1,204 @@
+use cosmwasm_std::{
+    coins, from_binary,
+    testing::{mock_dependencies, mock_env, mock_info},
+    to_binary, Addr, BankMsg, CosmosMsg, Order, Uint128, WasmMsg,
+};
+
+use crate::{
+    contract::execute,
+    execute,
+    msg::{
+        ExecuteMsg, ForwardRequestsMsg, GrantFeeMsg, ProcessSunsetMsg, SetPredecessorMsg,
+        SetSuccessorMsg, StartSunsetMsg,
+    },
+    state::{FeeGrantKey, ENTROPY_REQUESTS},
+    ContractError,
+};
+
+use super::{
+    default_instantiate, funded_dependencies, request_msg,
+    test_submit_entropy::{request_entropy, setup_contract},
+};
+
+#[test]
+fn requires_successor() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("creator", &[]);
+    let msg = ForwardRequestsMsg { limit: None };
+    let res = execute::forward_requests(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::NoSuccessor {});
+
+    let info = mock_info("not_creator", &[]);
+    let msg = SetSuccessorMsg {
+        address: Some("successor".to_string()),
+    };
+    let res = execute::set_successor(deps.as_mut(), env, info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn hands_off_requests_to_successor() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+
+    let info = mock_info("creator", &[]);
+    let msg = SetSuccessorMsg {
+        address: Some("successor".to_string()),
+    };
+    execute::set_successor(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ForwardRequestsMsg { limit: Some(2) };
+    let res = execute::forward_requests(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let (contract_addr, forward_msg, funds) = match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            msg,
+            funds,
+        }) => (contract_addr.clone(), msg.clone(), funds.clone()),
+        msg => panic!("unexpected message {:?}", msg),
+    };
+    assert_eq!(contract_addr, "successor");
+    assert_eq!(funds, coins(2200, "uluna"));
+
+    let remaining: Vec<_> = ENTROPY_REQUESTS
+        .keys(deps.as_ref().storage, None, None, Order::Ascending)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(remaining, vec![2]);
+
+    // Deliver the handoff to a fresh beacon.
+    let mut successor = mock_dependencies();
+    default_instantiate(successor.as_mut());
+    request_entropy(&mut successor, &mut env);
+
+    let forwarded: ExecuteMsg = from_binary(&forward_msg).unwrap();
+    let info = mock_info("predecessor", &funds);
+    let res = execute(
+        successor.as_mut(),
+        env.clone(),
+        info.clone(),
+        forwarded.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let creator = mock_info("creator", &[]);
+    let msg = SetPredecessorMsg {
+        address: Some("predecessor".to_string()),
+    };
+    execute::set_predecessor(successor.as_mut(), env.clone(), creator, msg).unwrap();
+
+    let underfunded = mock_info("predecessor", &coins(2199, "uluna"));
+    let res = execute(
+        successor.as_mut(),
+        env.clone(),
+        underfunded,
+        forwarded.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    // Anything sent beyond the bounties is refunded.
+    let overpaid = mock_info("predecessor", &coins(2300, "uluna"));
+    let res = execute(successor.as_mut(), env, overpaid, forwarded).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "predecessor".to_string(),
+            amount: coins(100, "uluna"),
+        })
+    );
+
+    let requests: Vec<_> = ENTROPY_REQUESTS
+        .range(successor.as_ref().storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, req)| req))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(requests.len(), 3);
+    for (id, req) in requests.into_iter().enumerate().skip(1) {
+        assert_eq!(req.id, id as u128);
+        assert_eq!(req.submitter, Addr::unchecked("requester"));
+        assert_eq!(req.callback_address, Addr::unchecked("callback_address"));
+        assert_eq!(req.callback_gas_limit, 1000);
+        assert_eq!(
+            req.callback_msg,
+            to_binary("callback_msg".as_bytes()).unwrap()
+        );
+        assert_eq!(req.submitted_bounty_amount, Uint128::from(1100u128));
+    }
+}
+
+#[test]
+fn refunds_forwarded_grants_to_sponsors() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("sponsor", &coins(1000, "uluna"));
+    let msg = GrantFeeMsg {
+        grantee: "requester".to_string(),
+        callback_requesters: None,
+        spend_limit: None,
+        per_request_limit: None,
+        expires_at: None,
+    };
+    execute::grant_fee(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let info = mock_info("requester", &[]);
+    execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg()).unwrap();
+
+    let creator = mock_info("creator", &[]);
+    let msg = SetSuccessorMsg {
+        address: Some("successor".to_string()),
+    };
+    execute::set_successor(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+    let msg = ForwardRequestsMsg { limit: None };
+    let res = execute::forward_requests(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+    let (forward_msg, funds) = match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => (msg.clone(), funds.clone()),
+        msg => panic!("unexpected message {:?}", msg),
+    };
+
+    let mut successor = mock_dependencies();
+    default_instantiate(successor.as_mut());
+    let msg = SetPredecessorMsg {
+        address: Some("predecessor".to_string()),
+    };
+    execute::set_predecessor(successor.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+    let info = mock_info("predecessor", &funds);
+    let forwarded: ExecuteMsg = from_binary(&forward_msg).unwrap();
+    execute(successor.as_mut(), env.clone(), info, forwarded).unwrap();
+
+    let req = ENTROPY_REQUESTS
+        .load(successor.as_ref().storage, 0)
+        .unwrap();
+    assert_eq!(
+        req.fee_grant,
+        Some(FeeGrantKey {
+            grantee: Addr::unchecked("requester"),
+            sponsor: Addr::unchecked("sponsor"),
+        })
+    );
+
+    // The grant stayed behind, so the bounty goes back to its sponsor.
+    execute::start_sunset(
+        successor.as_mut(),
+        env.clone(),
+        creator.clone(),
+        StartSunsetMsg {},
+    )
+    .unwrap();
+    let msg = ProcessSunsetMsg { limit: None };
+    let res = execute::process_sunset(successor.as_mut(), env, creator, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "sponsor".to_string(),
+            amount: coins(250, "uluna"),
+        })
+    );
+}
@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    Decimal, Empty, Env, OwnedDeps,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::SubmitEntropyMsg;
+
+use crate::{
+    execute,
+    msg::{CalculateFeeQuery, GasPriceHistoryQuery, SetGasPriceAdjustmentMsg},
+    query,
+    state::{GasPriceAdjustment, GasPriceRecord, STATE},
+    ContractError,
+};
+
+use super::{
+    funded_dependencies, test_sk,
+    test_submit_entropy::{request_entropy, setup_contract},
+};
+
+fn adjustment() -> GasPriceAdjustment {
+    GasPriceAdjustment {
+        target_queue_depth: 2,
+        min_gas_price: Decimal::percent(10),
+        max_gas_price: Decimal::percent(20),
+        max_change: Decimal::permille(125),
+    }
+}
+
+fn submit(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>, env: &Env) -> Decimal {
+    let last_entropy = STATE.load(&deps.storage).unwrap().last_entropy;
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), last_entropy.unwrap_or_default()).unwrap(),
+        request_ids: vec![],
+    };
+    let info = mock_info("submitter", &[]);
+    execute::submit_entropy(deps.as_mut(), env.clone(), info, msg).unwrap();
+    STATE.load(&deps.storage).unwrap().belief_gas_price
+}
+
+#[test]
+fn only_owner_sets_valid_adjustment() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let msg = SetGasPriceAdjustmentMsg {
+        adjustment: Some(adjustment()),
+    };
+    let info = mock_info("not_creator", &[]);
+    let res = execute::set_gas_price_adjustment(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let invalid = [
+        GasPriceAdjustment {
+            target_queue_depth: 0,
+            ..adjustment()
+        },
+        GasPriceAdjustment {
+            min_gas_price: Decimal::percent(30),
+            ..adjustment()
+        },
+        GasPriceAdjustment {
+            max_change: Decimal::percent(101),
+            ..adjustment()
+        },
+        GasPriceAdjustment {
+            min_gas_price: Decimal::zero(),
+            ..adjustment()
+        },
+    ];
+    for adjustment in invalid {
+        let msg = SetGasPriceAdjustmentMsg {
+            adjustment: Some(adjustment),
+        };
+        let info = mock_info("creator", &[]);
+        let res = execute::set_gas_price_adjustment(deps.as_mut(), env.clone(), info, msg);
+        assert_eq!(
+            res.unwrap_err(),
+            ContractError::InvalidGasPriceAdjustment {}
+        );
+    }
+
+    // The price is left alone until an adjustment is set.
+    assert_eq!(submit(&mut deps, &env), Decimal::percent(15));
+}
+
+#[test]
+fn adjusts_with_queue_depth() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let msg = SetGasPriceAdjustmentMsg {
+        adjustment: Some(adjustment()),
+    };
+    let info = mock_info("creator", &[]);
+    execute::set_gas_price_adjustment(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Half the target: the price falls by half the maximum change.
+    let price = submit(&mut deps, &env);
+    assert_eq!(price, Decimal::from_str("0.140625").unwrap());
+
+    // Twice the target: the price rises by the maximum change, even when
+    // adjusted again within the same block.
+    for _ in 0..4 {
+        request_entropy(&mut deps, &mut env);
+    }
+    let price = submit(&mut deps, &env);
+    assert_eq!(price, Decimal::from_str("0.158203125").unwrap());
+
+    let data = GasPriceHistoryQuery {
+        start_after: None,
+        limit: None,
+    };
+    let res = query::gas_price_history_query(deps.as_ref(), data).unwrap();
+    assert_eq!(
+        res.prices,
+        vec![
+            GasPriceRecord {
+                id: 0,
+                height: env.block.height,
+                gas_price: Decimal::from_str("0.140625").unwrap(),
+                queue_depth: 1,
+            },
+            GasPriceRecord {
+                id: 1,
+                height: env.block.height,
+                gas_price: Decimal::from_str("0.158203125").unwrap(),
+                queue_depth: 4,
+            },
+        ]
+    );
+
+    let data = GasPriceHistoryQuery {
+        start_after: Some(0),
+        limit: None,
+    };
+    let res = query::gas_price_history_query(deps.as_ref(), data).unwrap();
+    assert_eq!(res.prices.len(), 1);
+
+    // Requests are now priced at the adjusted gas price.
+    let data = CalculateFeeQuery {
+        callback_gas_limit: 1000,
+        denom: None,
+    };
+    let res = query::calculate_fee_query(deps.as_ref(), data).unwrap();
+    assert_eq!(res.fee, 258); // 1000 * 0.158203125 + 100
+}
+
+#[test]
+fn stays_within_bounds() {
+    let adjustment = GasPriceAdjustment {
+        target_queue_depth: 10,
+        min_gas_price: Decimal::percent(10),
+        max_gas_price: Decimal::percent(20),
+        max_change: Decimal::percent(50),
+    };
+    let price = Decimal::percent(15);
+    assert_eq!(adjustment.next_price(price, 10), price);
+    assert_eq!(adjustment.next_price(price, 100), Decimal::percent(20));
+    assert_eq!(adjustment.next_price(price, 0), Decimal::percent(10));
+}
+
+#[test]
+fn recovers_from_floor() {
+    let adjustment = GasPriceAdjustment {
+        target_queue_depth: 10,
+        min_gas_price: Decimal::permille(1),
+        max_gas_price: Decimal::percent(20),
+        max_change: Decimal::percent(100),
+    };
+    let price = adjustment.next_price(Decimal::percent(15), 0);
+    assert_eq!(price, Decimal::permille(1));
+    assert_eq!(adjustment.next_price(price, 1000), Decimal::permille(2));
+}
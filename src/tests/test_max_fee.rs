@@ -0,0 +1,107 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg, Uint128,
+};
+
+use crate::{
+    execute,
+    msg::{RequestEntropyMsg, SolvencyQuery},
+    query,
+    state::ENTROPY_REQUESTS,
+    ContractError,
+};
+
+use super::{default_instantiate, funded_dependencies, request_msg};
+
+fn capped_request_msg(max_fee: Option<u128>, bounty: Option<u128>) -> RequestEntropyMsg {
+    RequestEntropyMsg {
+        max_fee: max_fee.map(Uint128::from),
+        bounty: bounty.map(Uint128::from),
+        ..request_msg()
+    }
+}
+
+fn refund(to: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount, "uluna"),
+    })
+}
+
+#[test]
+fn rejects_fees_above_max() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("requester", &coins(1000, "uluna"));
+    let msg = capped_request_msg(Some(249), None);
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::FeeExceedsMax {
+            fee: Uint128::from(250u128),
+            max_fee: Uint128::from(249u128),
+        }
+    );
+
+    let msg = capped_request_msg(None, Some(249));
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::BountyBelowFee {
+            fee: Uint128::from(250u128)
+        }
+    );
+
+    let info = mock_info("requester", &coins(399, "uluna"));
+    let msg = capped_request_msg(None, Some(400));
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+}
+
+#[test]
+fn refunds_excess() {
+    let mut deps = funded_dependencies();
+    default_instantiate(deps.as_mut());
+
+    // Only the current fee is charged when a max fee is given.
+    let info = mock_info("requester", &coins(1000, "uluna"));
+    let msg = capped_request_msg(Some(300), None);
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("requester", 750));
+
+    // An explicit bounty is charged in full, and the rest can go elsewhere.
+    let msg = RequestEntropyMsg {
+        refund_address: Some("refunds".to_string()),
+        ..capped_request_msg(Some(300), Some(400))
+    };
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("refunds", 600));
+
+    // Without either, only the current fee is charged as well.
+    let msg = capped_request_msg(None, None);
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("requester", 750));
+
+    let bounties: Vec<_> = (0..3)
+        .map(|id| {
+            ENTROPY_REQUESTS
+                .load(deps.as_ref().storage, id)
+                .unwrap()
+                .submitted_bounty_amount
+        })
+        .collect();
+    assert_eq!(
+        bounties,
+        vec![
+            Uint128::from(250u128),
+            Uint128::from(400u128),
+            Uint128::from(250u128)
+        ]
+    );
+
+    let res =
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.bounties, Uint128::from(900u128));
+}
@@ -0,0 +1,189 @@
+use cosmwasm_std::{
+    coins,
+    testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, MockApi, MockQuerier,
+        MockStorage,
+    },
+    to_binary, Addr, Decimal, Empty, OwnedDeps, StdResult, Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
+
+use crate::{
+    contract::migrate,
+    migrations::v2_1,
+    msg::MigrateMsg,
+    state::{PauseFlags, CONFIG, ENTROPY_REQUESTS, LEDGERS, STATE, WHITELISTED_KEYS},
+    ContractError,
+};
+
+use super::{default_instantiate, test_pk};
+
+fn seed_v2_1(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>, version: &str) {
+    let storage = deps.as_mut().storage;
+    set_contract_version(storage, "entropiclabs/beacon", version).unwrap();
+
+    let state = v2_1::State {
+        last_entropy: Some(vec![1, 2, 3]),
+        belief_gas_price: Decimal::percent(15),
+        cur_request_id: 7,
+    };
+    v2_1::STATE.save(storage, &state).unwrap();
+
+    let cfg = v2_1::Config {
+        owner: Addr::unchecked("creator"),
+        whitelist_deposit_amt: Uint128::from(1000u128),
+        refund_increment_amt: Uint128::from(100u128),
+        key_activation_delay: 1,
+        protocol_fee: 100,
+        submitter_share: Decimal::percent(80),
+        native_denom: "uluna".to_string(),
+        paused: true,
+        permissioned: false,
+        test_mode: false,
+        subsidize_callbacks: false,
+    };
+    v2_1::CONFIG.save(storage, &cfg).unwrap();
+
+    let key = v2_1::KeyInfo {
+        holder: Addr::unchecked("submitter"),
+        deposit_amount: Uint128::from(1000u128),
+        refundable_amount: Uint128::from(200u128),
+        creation_height: 10,
+    };
+    v2_1::WHITELISTED_KEYS
+        .save(storage, test_pk().as_bytes(), &key)
+        .unwrap();
+
+    let request = v2_1::EntropyRequest {
+        id: 6,
+        callback_gas_limit: 1000,
+        callback_address: Addr::unchecked("callback_address"),
+        callback_msg: to_binary("callback_msg").unwrap(),
+        submitter: Addr::unchecked("requester"),
+        submitted_block_height: 12,
+        submitted_bounty_amount: Uint128::from(50u128),
+    };
+    v2_1::ENTROPY_REQUESTS.save(storage, 6, &request).unwrap();
+}
+
+#[test]
+fn upgrades_every_2x_layout() {
+    for version in ["2.0.0", "2.0.4", "2.1.0", "2.1.1"] {
+        // 1000 deposited by the key holder, 50 bounty, and 300 of revenue.
+        let mut deps = mock_dependencies_with_balance(&coins(1350, "uluna"));
+        seed_v2_1(&mut deps, version);
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res
+            .attributes
+            .iter()
+            .any(|a| a.key == "steps" && a.value == "2.2.0"));
+        assert_eq!(
+            get_contract_version(deps.as_ref().storage).unwrap().version,
+            env!("CARGO_PKG_VERSION")
+        );
+
+        let state = STATE.load(deps.as_ref().storage).unwrap();
+        assert_eq!(state.last_entropy, Some(vec![1, 2, 3]));
+        assert_eq!(state.cur_request_id, 7);
+        assert_eq!(state.cur_config_change_id, 0);
+        assert_eq!(state.pending_requests, 1);
+
+        let cfg = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert_eq!(cfg.owner, Some(Addr::unchecked("creator")));
+        assert_eq!(cfg.pause_flags, PauseFlags::all(true));
+        assert_eq!(cfg.config_timelock, 0);
+        assert!(!cfg.sunset);
+        assert_eq!(cfg.refund_increment_amt, Uint128::from(100u128));
+
+        let key = WHITELISTED_KEYS
+            .load(deps.as_ref().storage, test_pk().as_bytes())
+            .unwrap();
+        assert_eq!(key.holder, Addr::unchecked("submitter"));
+        assert_eq!(key.refundable_amount, Uint128::from(200u128));
+
+        let request = ENTROPY_REQUESTS.load(deps.as_ref().storage, 6).unwrap();
+        assert_eq!(request.submitter, Addr::unchecked("requester"));
+        assert_eq!(request.submitted_bounty_amount, Uint128::from(50u128));
+
+        let ledger = LEDGERS.load(deps.as_ref().storage, "uluna").unwrap();
+        assert_eq!(ledger.deposits, Uint128::from(1000u128));
+        assert_eq!(ledger.bounties, Uint128::from(50u128));
+        assert_eq!(ledger.revenue, Uint128::from(300u128));
+    }
+}
+
+#[test]
+fn seeds_sponsorship_when_subsidizing() {
+    let mut deps = mock_dependencies_with_balance(&coins(1350, "uluna"));
+    seed_v2_1(&mut deps, "2.1.1");
+    v2_1::CONFIG
+        .update(deps.as_mut().storage, |mut cfg| -> StdResult<_> {
+            cfg.subsidize_callbacks = true;
+            Ok(cfg)
+        })
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    // The surplus that paid for subsidies before now funds the pool.
+    let ledger = LEDGERS.load(deps.as_ref().storage, "uluna").unwrap();
+    assert_eq!(ledger.revenue, Uint128::zero());
+    assert_eq!(ledger.sponsorship, Uint128::from(300u128));
+    assert!(
+        CONFIG
+            .load(deps.as_ref().storage)
+            .unwrap()
+            .subsidize_callbacks
+    );
+}
+
+#[test]
+fn reruns_nothing_on_current_version() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "steps" && a.value.is_empty()));
+    assert!(CONFIG.load(deps.as_ref().storage).is_ok());
+}
+
+#[test]
+fn rejects_downgrades_and_unknown_versions() {
+    let mut deps = mock_dependencies();
+    seed_v2_1(&mut deps, "9.0.0");
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MigrationDowngrade {
+            from: "9.0.0".to_string(),
+            to: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+
+    for version in ["1.4.0", "two"] {
+        let mut deps = mock_dependencies();
+        seed_v2_1(&mut deps, version);
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+        assert_eq!(
+            res.unwrap_err(),
+            ContractError::UnsupportedMigrationVersion {
+                version: version.to_string(),
+            }
+        );
+    }
+
+    let mut deps = mock_dependencies();
+    seed_v2_1(&mut deps, "2.1.0");
+    set_contract_version(deps.as_mut().storage, "someone/else", "2.1.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {});
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InvalidMigrationContract {
+            name: "someone/else".to_string(),
+        }
+    );
+}
@@ -0,0 +1,129 @@
+use cosmwasm_std::{
+    testing::{mock_dependencies, mock_env, mock_info},
+    Addr, Decimal, DepsMut,
+};
+
+use crate::{
+    execute,
+    msg::{AcceptOwnershipMsg, CancelOwnershipTransferMsg, ProposeOwnerMsg, RenounceOwnershipMsg},
+    query, ContractError,
+};
+
+use super::default_instantiate;
+
+fn propose(deps: DepsMut, sender: &str, new_owner: &str) -> Result<(), ContractError> {
+    execute::propose_owner(
+        deps,
+        mock_env(),
+        mock_info(sender, &[]),
+        ProposeOwnerMsg {
+            new_owner: new_owner.to_string(),
+        },
+    )
+    .map(|_| ())
+}
+
+#[test]
+fn transfers_ownership() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    propose(deps.as_mut(), "creator", "multisig").unwrap();
+
+    let res = query::ownership_query(deps.as_ref()).unwrap();
+    assert_eq!(res.owner, Some(Addr::unchecked("creator")));
+    assert_eq!(res.pending_owner, Some(Addr::unchecked("multisig")));
+
+    let info = mock_info("multisig", &[]);
+    execute::accept_ownership(deps.as_mut(), mock_env(), info, AcceptOwnershipMsg {}).unwrap();
+
+    let res = query::ownership_query(deps.as_ref()).unwrap();
+    assert_eq!(res.owner, Some(Addr::unchecked("multisig")));
+    assert_eq!(res.pending_owner, None);
+
+    // The previous owner has lost its privileges.
+    let info = mock_info("creator", &[]);
+    let res = execute::update_gas_price(deps.as_mut(), mock_env(), info, Decimal::one());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("multisig", &[]);
+    let res = execute::update_gas_price(deps.as_mut(), mock_env(), info, Decimal::one());
+    assert!(res.is_ok());
+}
+
+#[test]
+fn rejects_unauthorized_proposal() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let res = propose(deps.as_mut(), "not_creator", "not_creator");
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn rejects_accept_from_other_address() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("multisig", &[]);
+    let res = execute::accept_ownership(deps.as_mut(), mock_env(), info, AcceptOwnershipMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::NoPendingOwner {});
+
+    propose(deps.as_mut(), "creator", "multisig").unwrap();
+
+    let info = mock_info("someone_else", &[]);
+    let res = execute::accept_ownership(deps.as_mut(), mock_env(), info, AcceptOwnershipMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn cancels_transfer() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    propose(deps.as_mut(), "creator", "multisig").unwrap();
+
+    let info = mock_info("multisig", &[]);
+    let res = execute::cancel_ownership_transfer(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        CancelOwnershipTransferMsg {},
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    execute::cancel_ownership_transfer(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        CancelOwnershipTransferMsg {},
+    )
+    .unwrap();
+
+    let info = mock_info("multisig", &[]);
+    let res = execute::accept_ownership(deps.as_mut(), mock_env(), info, AcceptOwnershipMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::NoPendingOwner {});
+}
+
+#[test]
+fn renounces_ownership() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    propose(deps.as_mut(), "creator", "multisig").unwrap();
+
+    let info = mock_info("creator", &[]);
+    execute::renounce_ownership(deps.as_mut(), mock_env(), info, RenounceOwnershipMsg {}).unwrap();
+
+    let res = query::ownership_query(deps.as_ref()).unwrap();
+    assert_eq!(res.owner, None);
+    assert_eq!(res.pending_owner, None);
+
+    let info = mock_info("multisig", &[]);
+    let res = execute::accept_ownership(deps.as_mut(), mock_env(), info, AcceptOwnershipMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::NoPendingOwner {});
+
+    let res = propose(deps.as_mut(), "creator", "multisig");
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
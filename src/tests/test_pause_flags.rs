@@ -0,0 +1,152 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_env, mock_info},
+    Decimal, DepsMut, Env, Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::{
+    beacon::UpdateConfigMsg,
+    provide::{AdminReturnDepositMsg, ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg},
+};
+
+use crate::{
+    execute,
+    msg::SetPausedMsg,
+    query,
+    state::PauseFlags,
+    tests::{
+        funded_dependencies, other_pk, request_msg, test_pk, test_sk,
+        test_submit_entropy::{request_entropy, setup_contract},
+    },
+    utils::empty_config_update,
+    ContractError,
+};
+
+fn set_paused(deps: DepsMut, env: Env, msg: SetPausedMsg) {
+    let info = mock_info("creator", &[]);
+    execute::set_paused(deps, env, info, msg).unwrap();
+}
+
+#[test]
+fn pausing_requests_allows_fulfilment_and_exit() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    set_paused(
+        deps.as_mut(),
+        env.clone(),
+        SetPausedMsg {
+            requests: Some(true),
+            submissions: None,
+            whitelisting: None,
+            withdrawals: None,
+        },
+    );
+
+    let info = mock_info("requester", &[coin(1100, "uluna")]);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    let info = mock_info("submitter", &[]);
+    let proof = Proof::new(&test_sk(), "").unwrap();
+    let msg = SubmitEntropyMsg {
+        proof,
+        request_ids: vec![Uint128::zero()],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    execute::reclaim_deposit(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn pausing_withdrawals_blocks_deposit_returns() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    set_paused(
+        deps.as_mut(),
+        env.clone(),
+        SetPausedMsg {
+            requests: None,
+            submissions: None,
+            whitelisting: None,
+            withdrawals: Some(true),
+        },
+    );
+
+    let info = mock_info("submitter", &[]);
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::reclaim_deposit(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    let info = mock_info("creator", &[]);
+    let msg = AdminReturnDepositMsg { key: test_pk() };
+    let res = execute::admin_return_deposit(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    // Other interactions are unaffected.
+    request_entropy(&mut deps, &mut env);
+}
+
+#[test]
+fn pausing_whitelisting_and_submissions() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    set_paused(
+        deps.as_mut(),
+        env.clone(),
+        SetPausedMsg {
+            requests: None,
+            submissions: Some(true),
+            whitelisting: Some(true),
+            withdrawals: None,
+        },
+    );
+
+    let info = mock_info("other_submitter", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: other_pk(),
+    };
+    let res = execute::whitelist_key(deps.as_mut(), env.clone(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+
+    let info = mock_info("submitter", &[]);
+    let proof = Proof::new(&test_sk(), "").unwrap();
+    let msg = SubmitEntropyMsg {
+        proof,
+        request_ids: vec![],
+    };
+    let res = execute::submit_entropy(deps.as_mut(), env, info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Paused {});
+}
+
+#[test]
+fn gas_price_updates_ignore_pause() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("creator", &[]);
+    let msg = UpdateConfigMsg {
+        paused: Some(true),
+        ..empty_config_update()
+    };
+    execute::update_config(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    assert_eq!(
+        query::pause_flags_query(deps.as_ref()).unwrap(),
+        PauseFlags::all(true)
+    );
+
+    let res = execute::update_gas_price(deps.as_mut(), env, info, Decimal::one());
+    assert!(res.is_ok());
+}
@@ -0,0 +1,125 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::{AdminReturnDepositMsg, ReclaimDepositMsg, SubmitEntropyMsg};
+
+use crate::{
+    execute,
+    msg::{ClaimRewardsMsg, SetPayoutAddressMsg, UnclaimedRewardsQuery},
+    query, ContractError,
+};
+
+use super::{funded_dependencies, test_pk, test_sk, test_submit_entropy::setup_contract};
+
+fn refund(to: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount, "uluna"),
+    })
+}
+
+#[test]
+fn only_holder_sets_payout_address() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let msg = SetPayoutAddressMsg {
+        public_key: test_pk(),
+        address: Some("cold_wallet".to_string()),
+    };
+    let info = mock_info("not_submitter", &[]);
+    let res = execute::set_payout_address(deps.as_mut(), env.clone(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("submitter", &[]);
+    execute::set_payout_address(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Clearing the address sends refunds to the holder again.
+    let msg = SetPayoutAddressMsg {
+        public_key: test_pk(),
+        address: None,
+    };
+    execute::set_payout_address(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::reclaim_deposit(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("submitter", 0));
+}
+
+#[test]
+fn pays_rewards_and_refunds_to_payout_address() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("submitter", &[]);
+    let msg = SetPayoutAddressMsg {
+        public_key: test_pk(),
+        address: Some("cold_wallet".to_string()),
+    };
+    execute::set_payout_address(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // Rewards accrue to the payout address, and the holder can claim them for it.
+    for (address, expected) in [("submitter", vec![]), ("cold_wallet", coins(880, "uluna"))] {
+        let data = UnclaimedRewardsQuery {
+            address: address.to_string(),
+        };
+        let res = query::unclaimed_rewards_query(deps.as_ref(), data).unwrap();
+        assert_eq!(res.rewards, expected);
+    }
+
+    let msg = ClaimRewardsMsg {
+        amount: Some(80u128.into()),
+        address: Some("cold_wallet".to_string()),
+        denom: None,
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("cold_wallet", 80));
+
+    // Changing the payout address does not redirect what has already accrued.
+    let msg = SetPayoutAddressMsg {
+        public_key: test_pk(),
+        address: Some("hot_wallet".to_string()),
+    };
+    execute::set_payout_address(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ClaimRewardsMsg {
+        amount: None,
+        address: Some("cold_wallet".to_string()),
+        denom: None,
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("cold_wallet", 800));
+
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::reclaim_deposit(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("hot_wallet", 1000));
+
+    // Keys returned by the owner are refunded the same way.
+    let mut deps = funded_dependencies();
+    setup_contract(&mut deps, &mut env);
+    let info = mock_info("submitter", &[]);
+    let msg = SetPayoutAddressMsg {
+        public_key: test_pk(),
+        address: Some("cold_wallet".to_string()),
+    };
+    execute::set_payout_address(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    let msg = AdminReturnDepositMsg { key: test_pk() };
+    let res = execute::admin_return_deposit(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(res.messages[0].msg, refund("cold_wallet", 1000));
+}
@@ -0,0 +1,125 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg, Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::{ReclaimDepositMsg, SubmitEntropyMsg, WhitelistPublicKeyMsg};
+
+use crate::{
+    execute,
+    msg::{GrantRoleMsg, WithdrawRevenueMsg},
+    query,
+    state::Role,
+    ContractError,
+};
+
+use super::{funded_dependencies, other_pk, test_sk, test_submit_entropy::setup_contract};
+
+#[test]
+fn accrues_revenue() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // 20% of the 1100 bounty stays with the protocol.
+    let res = query::protocol_revenue_query(deps.as_ref()).unwrap();
+    assert_eq!(res.revenue, coins(220, "uluna"));
+
+    // The locked part of a reclaimed deposit is forfeited.
+    let info = mock_info("key_holder", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: other_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ReclaimDepositMsg {
+        public_key: other_pk(),
+    };
+    execute::reclaim_deposit(deps.as_mut(), env, info, msg).unwrap();
+
+    let res = query::protocol_revenue_query(deps.as_ref()).unwrap();
+    assert_eq!(res.revenue, coins(1220, "uluna"));
+}
+
+#[test]
+fn withdraws_only_revenue() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let treasurer = mock_info("treasurer", &[]);
+    let msg = WithdrawRevenueMsg {
+        amount: None,
+        recipient: None,
+        denom: None,
+    };
+    let res = execute::withdraw_revenue(deps.as_mut(), env.clone(), treasurer.clone(), msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = GrantRoleMsg {
+        role: Role::Treasurer,
+        address: "treasurer".to_string(),
+    };
+    execute::grant_role(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // The key deposit is still held, but cannot be withdrawn.
+    let msg = WithdrawRevenueMsg {
+        amount: Some(Uint128::from(221u128)),
+        recipient: None,
+        denom: None,
+    };
+    let res = execute::withdraw_revenue(deps.as_mut(), env.clone(), treasurer.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientRevenue {
+            available: Uint128::from(220u128)
+        }
+    );
+
+    let msg = WithdrawRevenueMsg {
+        amount: Some(Uint128::from(20u128)),
+        recipient: Some("treasury".to_string()),
+        denom: None,
+    };
+    let res =
+        execute::withdraw_revenue(deps.as_mut(), env.clone(), treasurer.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasury".to_string(),
+            amount: coins(20, "uluna"),
+        })
+    );
+
+    let msg = WithdrawRevenueMsg {
+        amount: None,
+        recipient: None,
+        denom: None,
+    };
+    let res = execute::withdraw_revenue(deps.as_mut(), env, treasurer, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "treasurer".to_string(),
+            amount: coins(200, "uluna"),
+        })
+    );
+
+    let res = query::protocol_revenue_query(deps.as_ref()).unwrap();
+    assert_eq!(res.revenue, coins(0, "uluna"));
+}
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     coin, coins,
-    testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
     BankMsg, CosmosMsg, Empty, Env, OwnedDeps, Uint128,
 };
 use ecvrf_rs::{Proof, PublicKey};
@@ -15,7 +15,7 @@ use crate::{
     ContractError,
 };
 
-use super::{default_instantiate, test_pk};
+use super::{default_instantiate, funded_dependencies, test_pk};
 
 fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>, env: Env) {
     default_instantiate(deps.as_mut());
@@ -30,7 +30,7 @@ fn setup_contract(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>
 
 #[test]
 fn unwhitelists_key() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let env = mock_env();
     setup_contract(&mut deps, env.clone());
 
@@ -61,7 +61,7 @@ fn unwhitelists_key() {
 
 #[test]
 fn returns_deposit() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
 
     test_submit_entropy::setup_contract(&mut deps, &mut env);
@@ -106,7 +106,7 @@ fn returns_deposit() {
 
 #[test]
 fn respects_refund_increments() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let env = mock_env();
     setup_contract(&mut deps, env.clone());
 
@@ -138,7 +138,7 @@ fn respects_refund_increments() {
 
 #[test]
 fn rejects_unwhitelisted_keys() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let env = mock_env();
     setup_contract(&mut deps, env.clone());
 
@@ -153,7 +153,7 @@ fn rejects_unwhitelisted_keys() {
 
 #[test]
 fn rejects_unauthorized_claimers() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let env = mock_env();
     setup_contract(&mut deps, env.clone());
 
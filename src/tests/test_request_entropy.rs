@@ -10,7 +10,7 @@ use entropy_beacon_cosmos::{
     provide::{ActiveRequestInfo, ActiveRequestsQuery, WhitelistPublicKeyMsg},
 };
 
-use crate::{contract::instantiate, execute, query, ContractError};
+use crate::{contract::instantiate, execute, msg::FundSponsorshipMsg, query, ContractError};
 
 use super::{default_instantiate, test_pk};
 
@@ -40,7 +40,7 @@ fn requests_correctly() {
         callback_msg: to_binary("callback_msg".as_bytes()).unwrap(),
     };
 
-    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg.into());
     assert!(res.is_ok());
 
     let active_query_msg = ActiveRequestsQuery {
@@ -57,7 +57,7 @@ fn requests_correctly() {
             callback_address: Addr::unchecked("callback_address".to_string()),
             submitter: Addr::unchecked("requester".to_string()),
             submitted_block_height: env.block.height,
-            submitted_bounty_amount: Uint128::from(1100u128),
+            submitted_bounty_amount: Uint128::from(250u128),
         }
     );
 }
@@ -76,7 +76,7 @@ fn rejects_insufficient_funds() {
         callback_msg: to_binary("callback_msg".as_bytes()).unwrap(),
     };
 
-    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg.into());
     assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
 }
 
@@ -110,6 +110,9 @@ fn accepts_zero_if_subsidizing() {
     execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
     env.block.height += 1;
 
+    let info = mock_info("sponsor", &[coin(150, "uluna")]);
+    execute::fund_sponsorship(deps.as_mut(), env.clone(), info, FundSponsorshipMsg {}).unwrap();
+
     let info = mock_info("requester", &[]);
 
     let request_msg = RequestEntropyMsg {
@@ -118,7 +121,7 @@ fn accepts_zero_if_subsidizing() {
         callback_msg: to_binary("callback_msg".as_bytes()).unwrap(),
     };
 
-    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg.into());
     assert!(res.is_ok());
 
     let active_query_msg = ActiveRequestsQuery {
@@ -135,7 +138,8 @@ fn accepts_zero_if_subsidizing() {
             callback_address: Addr::unchecked("callback_address".to_string()),
             submitter: Addr::unchecked("requester".to_string()),
             submitted_block_height: env.block.height,
-            submitted_bounty_amount: Uint128::from(0u128),
+            // The sponsorship pool pays the callback gas into the bounty.
+            submitted_bounty_amount: Uint128::from(150u128),
         }
     );
 }
@@ -0,0 +1,109 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_env, mock_info},
+    BankMsg, Coin, CosmosMsg, Deps, Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::SubmitEntropyMsg;
+
+use crate::{
+    execute,
+    msg::{ClaimRewardsMsg, SolvencyQuery, UnclaimedRewardsQuery},
+    query, ContractError,
+};
+
+use super::{
+    funded_dependencies, test_sk,
+    test_submit_entropy::{request_entropy, setup_contract},
+};
+
+fn unclaimed(deps: Deps, address: &str) -> Vec<Coin> {
+    let data = UnclaimedRewardsQuery {
+        address: address.to_string(),
+    };
+    query::unclaimed_rewards_query(deps, data).unwrap().rewards
+}
+
+#[test]
+fn accrues_instead_of_paying() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    let res = execute::submit_entropy(deps.as_mut(), env, info, msg).unwrap();
+    assert!(res
+        .messages
+        .iter()
+        .all(|m| !matches!(m.msg, CosmosMsg::Bank(_))));
+
+    // 80% of two 1100 bounties.
+    assert_eq!(unclaimed(deps.as_ref(), "submitter"), coins(1760, "uluna"));
+    assert!(unclaimed(deps.as_ref(), "someone_else").is_empty());
+
+    let res =
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.rewards, Uint128::from(1760u128));
+}
+
+#[test]
+fn claims_all_or_part() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ClaimRewardsMsg {
+        amount: Some(Uint128::from(881u128)),
+        address: None,
+        denom: None,
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env.clone(), info.clone(), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::InsufficientRewards {
+            available: Uint128::from(880u128)
+        }
+    );
+
+    let msg = ClaimRewardsMsg {
+        amount: Some(Uint128::from(80u128)),
+        address: None,
+        denom: None,
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "submitter".to_string(),
+            amount: coins(80, "uluna"),
+        })
+    );
+    assert_eq!(unclaimed(deps.as_ref(), "submitter"), coins(800, "uluna"));
+
+    let msg = ClaimRewardsMsg {
+        amount: None,
+        address: None,
+        denom: None,
+    };
+    let res = execute::claim_rewards(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "submitter".to_string(),
+            amount: coins(800, "uluna"),
+        })
+    );
+    assert!(unclaimed(deps.as_ref(), "submitter").is_empty());
+}
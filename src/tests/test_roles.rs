@@ -0,0 +1,186 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_dependencies, mock_env, mock_info},
+    Addr, Decimal, DepsMut,
+};
+use entropy_beacon_cosmos::provide::{AdminReturnDepositMsg, WhitelistPublicKeyMsg};
+
+use crate::{
+    execute,
+    msg::{GrantRoleMsg, RevokeRoleMsg, RoleMembersQuery, RolesQuery, SetPausedMsg},
+    query,
+    state::Role,
+    ContractError,
+};
+
+use super::{default_instantiate, test_pk};
+
+fn grant(deps: DepsMut, role: Role, address: &str) {
+    let info = mock_info("creator", &[]);
+    let msg = GrantRoleMsg {
+        role,
+        address: address.to_string(),
+    };
+    execute::grant_role(deps, mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn grants_and_revokes_roles() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    grant(deps.as_mut(), Role::GasPriceUpdater, "bot");
+
+    let res = query::roles_query(
+        deps.as_ref(),
+        RolesQuery {
+            address: "bot".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.roles, vec![Role::GasPriceUpdater]);
+
+    let info = mock_info("bot", &[]);
+    let res = execute::update_gas_price(deps.as_mut(), mock_env(), info, Decimal::one());
+    assert!(res.is_ok());
+
+    let info = mock_info("creator", &[]);
+    let msg = RevokeRoleMsg {
+        role: Role::GasPriceUpdater,
+        address: "bot".to_string(),
+    };
+    execute::revoke_role(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+    let res = execute::revoke_role(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::RoleNotGranted {
+            role: "gas_price_updater".to_string(),
+            address: "bot".to_string(),
+        }
+    );
+
+    let info = mock_info("bot", &[]);
+    let res = execute::update_gas_price(deps.as_mut(), mock_env(), info, Decimal::one());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn owner_holds_every_role() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let res = query::roles_query(
+        deps.as_ref(),
+        RolesQuery {
+            address: "creator".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.roles, Role::all().to_vec());
+}
+
+#[test]
+fn rejects_unauthorized_grant() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+    grant(deps.as_mut(), Role::KeyManager, "manager");
+
+    let info = mock_info("manager", &[]);
+    let msg = GrantRoleMsg {
+        role: Role::KeyManager,
+        address: "someone_else".to_string(),
+    };
+    let res = execute::grant_role(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+}
+
+#[test]
+fn roles_are_scoped() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("submitter", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    grant(deps.as_mut(), Role::GasPriceUpdater, "bot");
+    grant(deps.as_mut(), Role::KeyManager, "manager");
+    grant(deps.as_mut(), Role::Pauser, "pauser");
+
+    let info = mock_info("bot", &[]);
+    let msg = AdminReturnDepositMsg { key: test_pk() };
+    let res = execute::admin_return_deposit(deps.as_mut(), env.clone(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("bot", &[]);
+    let res = execute::set_paused(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        SetPausedMsg {
+            requests: Some(true),
+            submissions: None,
+            whitelisting: None,
+            withdrawals: None,
+        },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("pauser", &[]);
+    let res = execute::set_paused(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        SetPausedMsg {
+            requests: Some(true),
+            submissions: None,
+            whitelisting: None,
+            withdrawals: None,
+        },
+    );
+    assert!(res.is_ok());
+    assert!(query::beacon_config_query(deps.as_ref()).unwrap().paused);
+
+    let info = mock_info("manager", &[]);
+    let res = execute::admin_return_deposit(deps.as_mut(), env, info, msg);
+    assert!(res.is_ok());
+}
+
+#[test]
+fn lists_role_members() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    grant(deps.as_mut(), Role::Pauser, "pauser_a");
+    grant(deps.as_mut(), Role::Pauser, "pauser_b");
+    grant(deps.as_mut(), Role::Treasurer, "treasurer");
+
+    let res = query::role_members_query(
+        deps.as_ref(),
+        RoleMembersQuery {
+            role: Role::Pauser,
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.members,
+        vec![Addr::unchecked("pauser_a"), Addr::unchecked("pauser_b")]
+    );
+
+    let res = query::role_members_query(
+        deps.as_ref(),
+        RoleMembersQuery {
+            role: Role::Pauser,
+            start_after: Some("pauser_a".to_string()),
+            limit: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(res.members, vec![Addr::unchecked("pauser_b")]);
+}
@@ -0,0 +1,88 @@
+use cosmwasm_std::{
+    coins,
+    testing::{mock_dependencies_with_balance, mock_env, mock_info},
+    Uint128,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::{ReclaimDepositMsg, SubmitEntropyMsg};
+
+use crate::{execute, msg::SolvencyQuery, query, utils::update_ledger_for_payout, ContractError};
+
+use super::{test_pk, test_sk, test_submit_entropy::setup_contract};
+
+#[test]
+fn reports_liabilities() {
+    // Exactly the 1000 deposit and the 1100 bounty.
+    let mut deps = mock_dependencies_with_balance(&coins(2100, "uluna"));
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let res =
+        query::solvency_query(deps.as_ref(), env.clone(), SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.denom, "uluna");
+    assert_eq!(res.balance, Uint128::from(2100u128));
+    assert_eq!(res.deposits, Uint128::from(1000u128));
+    assert_eq!(res.bounties, Uint128::from(1100u128));
+    assert_eq!(res.revenue, Uint128::zero());
+    assert_eq!(res.liabilities, Uint128::from(2100u128));
+    assert_eq!(res.shortfall, Uint128::zero());
+    assert!(res.solvent);
+
+    let mut deps = mock_dependencies_with_balance(&coins(2000, "uluna"));
+    setup_contract(&mut deps, &mut env);
+    let res = query::solvency_query(deps.as_ref(), env, SolvencyQuery { denom: None }).unwrap();
+    assert_eq!(res.shortfall, Uint128::from(100u128));
+    assert!(!res.solvent);
+}
+
+#[test]
+fn pays_out_when_already_short() {
+    // The beacon is 100 short of the 1000 deposit and the 1100 bounty.
+    let mut deps = mock_dependencies_with_balance(&coins(2000, "uluna"));
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    // Settling what is owed never changes the shortfall, so it is not blocked.
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    execute::reclaim_deposit(deps.as_mut(), env, info, msg).unwrap();
+}
+
+#[test]
+fn refuses_payments_that_deepen_shortfall() {
+    let mut deps = mock_dependencies_with_balance(&coins(2000, "uluna"));
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    // Paying out anything that is not settled from the ledger deepens the shortfall.
+    let res = update_ledger_for_payout(deps.as_mut(), &env, "uluna", Uint128::from(50u128), |_| {
+        Ok(())
+    });
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Insolvent {
+            denom: "uluna".to_string(),
+            shortfall: Uint128::from(150u128),
+        }
+    );
+
+    let res = update_ledger_for_payout(
+        deps.as_mut(),
+        &env,
+        "uluna",
+        Uint128::from(50u128),
+        |ledger| {
+            ledger.bounties = ledger.bounties.checked_sub(Uint128::from(50u128))?;
+            Ok(())
+        },
+    );
+    assert!(res.is_ok());
+}
@@ -0,0 +1,169 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg, Decimal, Deps, DepsMut, Uint128,
+};
+use entropy_beacon_cosmos::msg::InstantiateMsg;
+
+use crate::{
+    contract::instantiate,
+    execute,
+    msg::{
+        CalculateFeeQuery, DrainSponsorshipMsg, FundSponsorshipMsg, ProcessSunsetMsg,
+        SponsorshipPoolQuery, SponsorshipSpendsQuery, StartSunsetMsg,
+    },
+    query,
+    state::{SponsorshipSpend, ENTROPY_REQUESTS},
+    ContractError,
+};
+
+use super::{funded_dependencies, request_msg};
+
+fn subsidizing_instantiate(deps: DepsMut) {
+    let msg = InstantiateMsg {
+        whitelist_deposit_amt: Uint128::from(1000u128),
+        refund_increment_amt: Uint128::from(1000u128),
+        key_activation_delay: 1,
+        protocol_fee: 100,
+        submitter_share: 80,
+        native_denom: "uluna".to_string(),
+        whitelisted_keys: vec![],
+        belief_gas_price: Decimal::percent(15),
+        permissioned: false,
+        test_mode: false,
+        subsidize_callbacks: true,
+    };
+    instantiate(deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+}
+
+fn fee(deps: Deps) -> u64 {
+    let data = CalculateFeeQuery {
+        callback_gas_limit: 1000,
+        denom: None,
+    };
+    query::calculate_fee_query(deps, data).unwrap().fee
+}
+
+fn pool(deps: Deps) -> Uint128 {
+    let data = SponsorshipPoolQuery { denom: None };
+    query::sponsorship_pool_query(deps, data).unwrap().balance
+}
+
+fn send(to: &str, amount: u128) -> CosmosMsg {
+    CosmosMsg::Bank(BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount, "uluna"),
+    })
+}
+
+#[test]
+fn anyone_funds_the_pool() {
+    let mut deps = funded_dependencies();
+    subsidizing_instantiate(deps.as_mut());
+
+    let info = mock_info("sponsor", &[]);
+    let res = execute::fund_sponsorship(deps.as_mut(), mock_env(), info, FundSponsorshipMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    let info = mock_info("sponsor", &coins(200, "uluna"));
+    execute::fund_sponsorship(deps.as_mut(), mock_env(), info, FundSponsorshipMsg {}).unwrap();
+    let info = mock_info("other_sponsor", &coins(100, "uluna"));
+    execute::fund_sponsorship(deps.as_mut(), mock_env(), info, FundSponsorshipMsg {}).unwrap();
+
+    let res =
+        query::sponsorship_pool_query(deps.as_ref(), SponsorshipPoolQuery { denom: None }).unwrap();
+    assert_eq!(res.denom, "uluna");
+    assert_eq!(res.balance, Uint128::from(300u128));
+    assert!(res.subsidize_callbacks);
+}
+
+#[test]
+fn draws_gas_from_pool_until_dry() {
+    let mut deps = funded_dependencies();
+    subsidizing_instantiate(deps.as_mut());
+
+    // Without a funded pool, requesters pay for gas themselves.
+    assert_eq!(fee(deps.as_ref()), 250); // 1000 * 0.15 + 100
+
+    let info = mock_info("sponsor", &coins(200, "uluna"));
+    execute::fund_sponsorship(deps.as_mut(), mock_env(), info, FundSponsorshipMsg {}).unwrap();
+    assert_eq!(fee(deps.as_ref()), 100);
+
+    let info = mock_info("requester", &coins(100, "uluna"));
+    execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg()).unwrap();
+    let request = ENTROPY_REQUESTS.load(&deps.storage, 0).unwrap();
+    assert_eq!(request.submitted_bounty_amount, Uint128::from(250u128));
+    assert_eq!(pool(deps.as_ref()), Uint128::from(50u128));
+
+    // The pool no longer covers the gas of a whole callback.
+    assert_eq!(fee(deps.as_ref()), 250);
+    let info = mock_info("requester", &coins(100, "uluna"));
+    let res = execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+
+    let info = mock_info("requester", &[coin(250, "uluna")]);
+    execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg()).unwrap();
+    assert_eq!(pool(deps.as_ref()), Uint128::from(50u128));
+
+    let data = SponsorshipSpendsQuery {
+        start_after: None,
+        limit: None,
+    };
+    let res = query::sponsorship_spends_query(deps.as_ref(), data).unwrap();
+    assert_eq!(
+        res.spends,
+        vec![SponsorshipSpend {
+            request_id: Uint128::zero(),
+            height: mock_env().block.height,
+            denom: "uluna".to_string(),
+            amount: Uint128::from(150u128),
+        }]
+    );
+}
+
+#[test]
+fn returns_unspent_gas_and_drains_after_sunset() {
+    let mut deps = funded_dependencies();
+    subsidizing_instantiate(deps.as_mut());
+
+    let info = mock_info("sponsor", &coins(200, "uluna"));
+    execute::fund_sponsorship(deps.as_mut(), mock_env(), info, FundSponsorshipMsg {}).unwrap();
+    let info = mock_info("requester", &coins(100, "uluna"));
+    execute::request_entropy(deps.as_mut(), mock_env(), info, request_msg()).unwrap();
+    assert_eq!(pool(deps.as_ref()), Uint128::from(50u128));
+
+    let creator = mock_info("creator", &[]);
+    let msg = DrainSponsorshipMsg {
+        recipient: None,
+        denom: None,
+    };
+    let res = execute::drain_sponsorship(deps.as_mut(), mock_env(), creator.clone(), msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::NotInSunset {});
+    execute::start_sunset(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        StartSunsetMsg {},
+    )
+    .unwrap();
+
+    // Only what the requester paid is refunded, and the gas goes back to the pool.
+    let data = ProcessSunsetMsg { limit: None };
+    let res = execute::process_sunset(deps.as_mut(), mock_env(), creator.clone(), data).unwrap();
+    assert_eq!(res.messages[0].msg, send("requester", 100));
+    assert_eq!(pool(deps.as_ref()), Uint128::from(200u128));
+    let data = SponsorshipSpendsQuery {
+        start_after: None,
+        limit: None,
+    };
+    let res = query::sponsorship_spends_query(deps.as_ref(), data).unwrap();
+    assert!(res.spends.is_empty());
+
+    let info = mock_info("not_creator", &[]);
+    let res = execute::drain_sponsorship(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let res = execute::drain_sponsorship(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    assert_eq!(res.messages[0].msg, send("creator", 200));
+    assert_eq!(pool(deps.as_ref()), Uint128::zero());
+}
@@ -0,0 +1,248 @@
+use cosmwasm_std::{
+    coin,
+    testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    Addr, Decimal, Empty, OwnedDeps,
+};
+use ecvrf_rs::Proof;
+use entropy_beacon_cosmos::provide::{SubmitEntropyMsg, WhitelistPublicKeyMsg};
+
+use crate::{
+    execute,
+    msg::{
+        CreditQuery, DepositCreditMsg, ExportStateQuery, FundSponsorshipMsg, GrantFeeMsg,
+        GrantRoleMsg, ImportStateMsg, ProposeOwnerMsg, SolvencyQuery, StateExport,
+    },
+    query,
+    state::Role,
+    ContractError,
+};
+
+use super::{
+    default_instantiate, funded_dependencies, other_pk, test_sk,
+    test_submit_entropy::{request_entropy, setup_contract},
+};
+
+/// Exports the page following the cursors of `data`, and moves them past it.
+fn next_page(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
+    data: &mut ExportStateQuery,
+) -> StateExport {
+    let page = query::export_state_query(deps.as_ref(), data.clone()).unwrap();
+    if let Some(key) = page.keys.last() {
+        data.start_after_key = Some(key.public_key);
+    }
+    if let Some(req) = page.requests.last() {
+        data.start_after_request = Some(req.id.into());
+    }
+    if let Some(reward) = page.rewards.last() {
+        data.start_after_reward = Some((reward.address.to_string(), reward.denom.clone()));
+    }
+    if let Some(credit) = page.credits.last() {
+        data.start_after_credit = Some((credit.address.to_string(), credit.denom.clone()));
+    }
+    if let Some(grant) = page.grants.last() {
+        data.start_after_grant = Some((
+            grant.grantee.to_string(),
+            grant.sponsor.to_string(),
+            grant.denom.clone(),
+        ));
+    }
+    if let Some(spend) = page.sponsorship_spends.last() {
+        data.start_after_spend = Some(spend.request_id);
+    }
+    if let Some(record) = page.gas_price_history.last() {
+        data.start_after_gas_price = Some(record.id);
+    }
+    page
+}
+
+fn export_all(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>) -> StateExport {
+    next_page(deps, &mut export_query(None))
+}
+
+fn export_query(limit: Option<u32>) -> ExportStateQuery {
+    ExportStateQuery {
+        start_after_key: None,
+        start_after_request: None,
+        start_after_reward: None,
+        start_after_credit: None,
+        start_after_grant: None,
+        start_after_spend: None,
+        start_after_gas_price: None,
+        limit,
+    }
+}
+
+#[test]
+fn round_trips() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+
+    // Fulfilling the requests accrues rewards and revenue.
+    let info = mock_info("submitter", &[]);
+    let msg = SubmitEntropyMsg {
+        proof: Proof::new(&test_sk(), "").unwrap(),
+        request_ids: vec![],
+    };
+    execute::submit_entropy(deps.as_mut(), env.clone(), info, msg).unwrap();
+    request_entropy(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+
+    let info = mock_info("requester", &[coin(500, "uluna")]);
+    let msg = DepositCreditMsg { address: None };
+    execute::deposit_credit(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("sponsor", &[coin(700, "uluna")]);
+    let msg = GrantFeeMsg {
+        grantee: "requester".to_string(),
+        callback_requesters: None,
+        spend_limit: None,
+        per_request_limit: None,
+        expires_at: None,
+    };
+    execute::grant_fee(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("key_holder", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: other_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    execute::update_gas_price(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Decimal::percent(30),
+    )
+    .unwrap();
+    let msg = GrantRoleMsg {
+        role: Role::Pauser,
+        address: "pauser".to_string(),
+    };
+    execute::grant_role(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ProposeOwnerMsg {
+        new_owner: "new_owner".to_string(),
+    };
+    execute::propose_owner(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let mut restored = funded_dependencies();
+    default_instantiate(restored.as_mut());
+
+    let info = mock_info("creator", &[]);
+    let mut data = export_query(Some(1));
+    loop {
+        let page = next_page(&deps, &mut data);
+        let done = page.keys.is_empty()
+            && page.requests.is_empty()
+            && page.rewards.is_empty()
+            && page.credits.is_empty()
+            && page.grants.is_empty()
+            && page.sponsorship_spends.is_empty()
+            && page.gas_price_history.is_empty();
+        let msg = ImportStateMsg {
+            export: Box::new(page),
+            finalize: done,
+        };
+        execute::import_state(restored.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        if done {
+            break;
+        }
+    }
+
+    let original = export_all(&deps);
+    assert_eq!(original.keys.len(), 2);
+    assert_eq!(original.requests.len(), 2);
+    assert_eq!(original.rewards.len(), 1);
+    assert_eq!(original.credits.len(), 1);
+    assert_eq!(original.grants.len(), 1);
+    assert_eq!(original.roles.len(), 1);
+    assert_eq!(original.pending_owner, Some(Addr::unchecked("new_owner")));
+    assert_eq!(export_all(&restored), original);
+
+    // Everything that is owed is carried over, along with what is not.
+    let solvency = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>| {
+        query::solvency_query(deps.as_ref(), mock_env(), SolvencyQuery { denom: None }).unwrap()
+    };
+    let res = solvency(&restored);
+    assert_eq!(res, solvency(&deps));
+    assert!(!res.revenue.is_zero() && !res.rewards.is_zero());
+    let credit = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>| {
+        let data = CreditQuery {
+            address: "requester".to_string(),
+        };
+        query::credit_query(deps.as_ref(), data).unwrap()
+    };
+    assert_eq!(credit(&restored).credits, vec![coin(500, "uluna")]);
+    assert_eq!(credit(&restored), credit(&deps));
+
+    let msg = ImportStateMsg {
+        export: Box::new(original),
+        finalize: false,
+    };
+    let res = execute::import_state(restored.as_mut(), env, info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::ImportClosed {});
+}
+
+#[test]
+fn only_owner_closes() {
+    let mut deps = funded_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+    let export = export_all(&deps);
+
+    // Funding a fresh beacon does not close the import.
+    let info = mock_info("anyone", &[coin(1, "uluna")]);
+    execute::fund_sponsorship(deps.as_mut(), env.clone(), info, FundSponsorshipMsg {}).unwrap();
+
+    let info = mock_info("creator", &[]);
+    let msg = ImportStateMsg {
+        export: Box::new(export.clone()),
+        finalize: true,
+    };
+    execute::import_state(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    let msg = ImportStateMsg {
+        export: Box::new(export),
+        finalize: false,
+    };
+    let res = execute::import_state(deps.as_mut(), env, info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::ImportClosed {});
+}
+
+#[test]
+fn rejects_invalid_imports() {
+    let source = {
+        let mut deps = mock_dependencies();
+        default_instantiate(deps.as_mut());
+        export_all(&deps)
+    };
+
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("not_creator", &[]);
+    let msg = ImportStateMsg {
+        export: Box::new(source.clone()),
+        finalize: true,
+    };
+    let res = execute::import_state(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    // Exports from before fee denoms were included are not accepted.
+    let msg = ImportStateMsg {
+        export: Box::new(StateExport {
+            version: 1,
+            ..source
+        }),
+        finalize: true,
+    };
+    let res = execute::import_state(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedExportVersion { version: 1 }
+    );
+}
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     coin,
-    testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+    testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage},
     to_binary, Addr, Empty, Env, OwnedDeps, Uint128,
 };
 
@@ -10,9 +10,9 @@ use entropy_beacon_cosmos::{
     provide::{SubmitEntropyMsg, WhitelistPublicKeyMsg},
 };
 
-use crate::{execute, query, ContractError};
+use crate::{execute, msg, query, ContractError};
 
-use super::{default_instantiate, test_pk, test_sk};
+use super::{default_instantiate, funded_dependencies, test_pk, test_sk};
 
 pub fn request_entropy(
     deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier, Empty>,
@@ -20,11 +20,14 @@ pub fn request_entropy(
 ) {
     let info = mock_info("requester", &[coin(1100, "uluna")]);
 
-    let request_msg = RequestEntropyMsg {
+    let mut request_msg: msg::RequestEntropyMsg = RequestEntropyMsg {
         callback_gas_limit: 1000,
         callback_address: Addr::unchecked("callback_address".to_string()),
         callback_msg: to_binary("callback_msg".as_bytes()).unwrap(),
-    };
+    }
+    .into();
+    // Everything that is sent is paid as the bounty.
+    request_msg.bounty = Some(Uint128::from(1100u128));
 
     execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg).unwrap();
 }
@@ -48,7 +51,7 @@ pub fn setup_contract(
 
 #[test]
 fn submits_correctly() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -69,7 +72,7 @@ fn submits_correctly() {
 
 #[test]
 fn rejects_wrong_message() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -87,7 +90,7 @@ fn rejects_wrong_message() {
 
 #[test]
 fn rejects_inactive_keys() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
     env.block.height -= 1;
@@ -111,7 +114,7 @@ fn rejects_inactive_keys() {
 
 #[test]
 fn rejects_invalid_keys() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -130,7 +133,7 @@ fn rejects_invalid_keys() {
 
 #[test]
 fn rejects_unauthorized_sender() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -148,7 +151,7 @@ fn rejects_unauthorized_sender() {
 
 #[test]
 fn rejects_invalid_proofs() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -170,7 +173,7 @@ fn rejects_invalid_proofs() {
 
 #[test]
 fn submit_all_multiple_request_ids() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
     request_entropy(&mut deps, &mut env);
@@ -187,12 +190,12 @@ fn submit_all_multiple_request_ids() {
     assert!(res.is_ok());
 
     let res = res.unwrap();
-    assert_eq!(res.messages.len(), 3);
+    assert_eq!(res.messages.len(), 2);
 }
 
 #[test]
 fn submit_none_multiple_request_ids() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
     request_entropy(&mut deps, &mut env);
@@ -209,12 +212,12 @@ fn submit_none_multiple_request_ids() {
     assert!(res.is_ok());
 
     let res = res.unwrap();
-    assert_eq!(res.messages.len(), 3);
+    assert_eq!(res.messages.len(), 2);
 }
 
 #[test]
 fn submit_one_multiple_request_ids() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
     request_entropy(&mut deps, &mut env);
@@ -231,12 +234,12 @@ fn submit_one_multiple_request_ids() {
     assert!(res.is_ok());
 
     let res = res.unwrap();
-    assert_eq!(res.messages.len(), 2);
+    assert_eq!(res.messages.len(), 1);
 }
 
 #[test]
 fn errors_on_invalid_request_id() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
     request_entropy(&mut deps, &mut env);
@@ -258,7 +261,7 @@ fn errors_on_invalid_request_id() {
 
 #[test]
 fn max_512_requests_per_tx() {
-    let mut deps = mock_dependencies();
+    let mut deps = funded_dependencies();
     let mut env = mock_env();
     setup_contract(&mut deps, &mut env);
 
@@ -278,5 +281,5 @@ fn max_512_requests_per_tx() {
     assert!(res.is_ok());
 
     let res = res.unwrap();
-    assert_eq!(res.messages.len(), 513);
+    assert_eq!(res.messages.len(), 512);
 }
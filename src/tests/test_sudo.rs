@@ -0,0 +1,105 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_dependencies, mock_env, mock_info},
+    Addr, BankMsg, CosmosMsg,
+};
+use entropy_beacon_cosmos::provide::{AdminReturnDepositMsg, WhitelistPublicKeyMsg};
+
+use crate::{
+    contract::sudo,
+    execute,
+    msg::{ProposeOwnerMsg, SetPausedMsg, SudoMsg, SudoUpdateOwnerMsg},
+    query,
+    state::{PauseFlags, WHITELISTED_KEYS},
+};
+
+use super::{default_instantiate, test_pk};
+
+#[test]
+fn pauses_and_unpauses() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let msg = SudoMsg::SetPaused(SetPausedMsg {
+        requests: Some(true),
+        submissions: Some(true),
+        whitelisting: Some(true),
+        withdrawals: Some(true),
+    });
+    sudo(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        query::pause_flags_query(deps.as_ref()).unwrap(),
+        PauseFlags::all(true)
+    );
+
+    let msg = SudoMsg::SetPaused(SetPausedMsg {
+        requests: Some(false),
+        submissions: None,
+        whitelisting: None,
+        withdrawals: None,
+    });
+    sudo(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        query::pause_flags_query(deps.as_ref()).unwrap(),
+        PauseFlags {
+            requests: false,
+            ..PauseFlags::all(true)
+        }
+    );
+}
+
+#[test]
+fn replaces_owner() {
+    let mut deps = mock_dependencies();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("creator", &[]);
+    let msg = ProposeOwnerMsg {
+        new_owner: "multisig".to_string(),
+    };
+    execute::propose_owner(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = SudoMsg::UpdateOwner(SudoUpdateOwnerMsg {
+        new_owner: "governance".to_string(),
+    });
+    sudo(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let res = query::ownership_query(deps.as_ref()).unwrap();
+    assert_eq!(res.owner, Some(Addr::unchecked("governance")));
+    assert_eq!(res.pending_owner, None);
+}
+
+#[test]
+fn force_returns_deposit_while_paused() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    default_instantiate(deps.as_mut());
+
+    let info = mock_info("submitter", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: test_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = SudoMsg::SetPaused(SetPausedMsg {
+        requests: None,
+        submissions: None,
+        whitelisting: None,
+        withdrawals: Some(true),
+    });
+    sudo(deps.as_mut(), env.clone(), msg).unwrap();
+
+    let msg = SudoMsg::ForceReturnDeposit(AdminReturnDepositMsg { key: test_pk() });
+    let res = sudo(deps.as_mut(), env, msg).unwrap();
+
+    assert!(WHITELISTED_KEYS
+        .load(deps.as_ref().storage, test_pk().as_bytes())
+        .is_err());
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "submitter".to_string(),
+            amount: coins(1000, "uluna"),
+        })
+    );
+}
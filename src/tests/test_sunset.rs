@@ -0,0 +1,142 @@
+use cosmwasm_std::{
+    coin, coins,
+    testing::{mock_env, mock_info},
+    BankMsg, CosmosMsg,
+};
+use entropy_beacon_cosmos::provide::{ReclaimDepositMsg, WhitelistPublicKeyMsg};
+
+use crate::{
+    execute,
+    msg::{ProcessSunsetMsg, StartSunsetMsg},
+    query,
+    state::STATE,
+    ContractError,
+};
+
+use super::{
+    funded_dependencies, other_pk, request_msg, test_pk,
+    test_submit_entropy::{request_entropy, setup_contract},
+};
+
+#[test]
+fn blocks_new_requests_and_whitelisting() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("not_creator", &[]);
+    let res = execute::start_sunset(deps.as_mut(), env.clone(), info, StartSunsetMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let res =
+        execute::start_sunset(deps.as_mut(), env.clone(), info.clone(), StartSunsetMsg {}).unwrap();
+    assert_eq!(res.events[0].ty, "sunset_started");
+
+    let res = execute::start_sunset(deps.as_mut(), env.clone(), info, StartSunsetMsg {});
+    assert_eq!(res.unwrap_err(), ContractError::Sunset {});
+
+    let info = mock_info("requester", &[coin(1100, "uluna")]);
+    let res = execute::request_entropy(deps.as_mut(), env.clone(), info, request_msg());
+    assert_eq!(res.unwrap_err(), ContractError::Sunset {});
+
+    let info = mock_info("submitter", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: other_pk(),
+    };
+    let res = execute::whitelist_key(deps.as_mut(), env, info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::Sunset {});
+}
+
+#[test]
+fn refunds_everything_in_batches() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+    request_entropy(&mut deps, &mut env);
+
+    let info = mock_info("key_holder", &[coin(1000, "uluna")]);
+    let msg = WhitelistPublicKeyMsg {
+        public_key: other_pk(),
+    };
+    execute::whitelist_key(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info("anyone", &[]);
+    let res = execute::process_sunset(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ProcessSunsetMsg { limit: None },
+    );
+    assert_eq!(res.unwrap_err(), ContractError::NotInSunset {});
+
+    let creator = mock_info("creator", &[]);
+    execute::start_sunset(deps.as_mut(), env.clone(), creator, StartSunsetMsg {}).unwrap();
+
+    let res = execute::process_sunset(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ProcessSunsetMsg { limit: Some(3) },
+    )
+    .unwrap();
+    let refund = |to: &str, amount: u128| {
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount, "uluna"),
+        })
+    };
+    let msgs: Vec<_> = res.messages.iter().map(|m| m.msg.clone()).collect();
+    assert_eq!(msgs.len(), 3);
+    assert_eq!(msgs[0], refund("requester", 1100));
+    assert_eq!(msgs[1], refund("requester", 1100));
+    assert!(!query::sunset_status_query(deps.as_ref()).unwrap().complete);
+    assert_eq!(STATE.load(&deps.storage).unwrap().pending_requests, 0);
+
+    let res = execute::process_sunset(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ProcessSunsetMsg { limit: Some(3) },
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let mut returned: Vec<_> = vec![msgs[2].clone(), res.messages[0].msg.clone()];
+    returned.sort_by_key(|m| format!("{:?}", m));
+    assert_eq!(
+        returned,
+        vec![refund("key_holder", 1000), refund("submitter", 1000)]
+    );
+
+    let status = query::sunset_status_query(deps.as_ref()).unwrap();
+    assert!(status.sunset);
+    assert!(status.complete);
+
+    let res = execute::process_sunset(deps.as_mut(), env, info, ProcessSunsetMsg { limit: None })
+        .unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn reclaims_full_deposit() {
+    let mut deps = funded_dependencies();
+    let mut env = mock_env();
+    setup_contract(&mut deps, &mut env);
+
+    let info = mock_info("creator", &[]);
+    execute::start_sunset(deps.as_mut(), env.clone(), info, StartSunsetMsg {}).unwrap();
+
+    let info = mock_info("submitter", &[]);
+    let msg = ReclaimDepositMsg {
+        public_key: test_pk(),
+    };
+    let res = execute::reclaim_deposit(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "submitter".to_string(),
+            amount: coins(1000, "uluna"),
+        })
+    );
+}
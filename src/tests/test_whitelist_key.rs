@@ -93,7 +93,12 @@ fn rejects_without_deposit() {
 
     let info = mock_info("executor", &[coin(1000, "uatom")]);
     let res = execute::whitelist_key(deps.as_mut(), env, info, msg);
-    assert_eq!(res.unwrap_err(), ContractError::InsufficientFunds {});
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::UnsupportedDenom {
+            denom: "uatom".to_string()
+        }
+    );
 }
 
 #[test]
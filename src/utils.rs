@@ -1,8 +1,17 @@
-use cosmwasm_std::{Deps, Env};
+use cosmwasm_std::{
+    to_binary, to_vec, Addr, BankMsg, Coin, CosmosMsg, Deps, DepsMut, Env, Order, StdResult,
+    Storage, Uint128, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
 use ecvrf_rs::PublicKey;
+use entropy_beacon_cosmos::beacon::UpdateConfigMsg;
+use serde::Serialize;
 
 use crate::{
-    state::{WHITELISTED_KEYS, Config},
+    state::{
+        AuditChange, AuditEntry, Config, DenomDeposit, ForeignDenomPolicy, Ledger, Role, AUDIT_LOG,
+        FEE_DENOMS, LEDGERS, ROLES, WHITELISTED_KEYS,
+    },
     ContractError,
 };
 
@@ -22,7 +31,8 @@ pub fn check_key(
 ) -> Result<u64, ContractError> {
     let created_time = WHITELISTED_KEYS
         .load(deps.storage, key.as_bytes())
-        .map_err(|_| ContractError::KeyNotWhitelisted {})?.creation_height;
+        .map_err(|_| ContractError::KeyNotWhitelisted {})?
+        .creation_height;
     if created_time + cfg.key_activation_delay <= env.block.height {
         Ok(created_time + cfg.key_activation_delay)
     } else {
@@ -31,3 +41,383 @@ pub fn check_key(
         })
     }
 }
+
+/// Ensures that the sender is the current owner of the contract.
+/// Always fails once ownership has been renounced.
+pub fn check_owner(cfg: &Config, sender: &Addr) -> Result<(), ContractError> {
+    match &cfg.owner {
+        Some(owner) if owner == sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+/// Checks whether an address holds a role, either through an explicit
+/// grant or by being the owner of the contract.
+pub fn has_role(deps: &Deps, cfg: &Config, role: Role, addr: &Addr) -> bool {
+    cfg.owner.as_ref() == Some(addr) || ROLES.has(deps.storage, (role.as_str(), addr))
+}
+
+/// Ensures that the sender holds the given role.
+pub fn check_role(
+    deps: &Deps,
+    cfg: &Config,
+    role: Role,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    if has_role(deps, cfg, role, sender) {
+        Ok(())
+    } else {
+        Err(ContractError::Unauthorized {})
+    }
+}
+
+/// An `UpdateConfigMsg` that leaves every field unchanged.
+pub fn empty_config_update() -> UpdateConfigMsg {
+    UpdateConfigMsg {
+        whitelist_deposit_amt: None,
+        refund_increment_amt: None,
+        key_activation_delay: None,
+        protocol_fee: None,
+        submitter_share: None,
+        native_denom: None,
+        paused: None,
+        permissioned: None,
+        subsidize_callbacks: None,
+    }
+}
+
+/// Describes a single changed value for the audit log.
+pub fn audit_change<T: Serialize>(
+    field: impl Into<String>,
+    old_value: &T,
+    new_value: &T,
+) -> StdResult<AuditChange> {
+    Ok(AuditChange {
+        field: field.into(),
+        old_value: String::from_utf8_lossy(&to_vec(old_value)?).into_owned(),
+        new_value: String::from_utf8_lossy(&to_vec(new_value)?).into_owned(),
+    })
+}
+
+/// Lists every field that differs between two versions of the config.
+pub fn config_changes(old: &Config, new: &Config) -> StdResult<Vec<AuditChange>> {
+    let mut changes = vec![];
+    macro_rules! diff {
+        ($($field:ident),* $(,)?) => {
+            $(
+                if old.$field != new.$field {
+                    changes.push(audit_change(stringify!($field), &old.$field, &new.$field)?);
+                }
+            )*
+        };
+    }
+    diff!(
+        owner,
+        whitelist_deposit_amt,
+        refund_increment_amt,
+        key_activation_delay,
+        protocol_fee,
+        submitter_share,
+        native_denom,
+        pause_flags,
+        permissioned,
+        test_mode,
+        subsidize_callbacks,
+        config_timelock,
+        sunset,
+        successor,
+        predecessor,
+        fee_split,
+        foreign_denoms,
+        gas_price_adjustment,
+    );
+    Ok(changes)
+}
+
+/// Applies a change to the funds owed in a denom, returning the updated ledger.
+pub fn update_ledger(
+    storage: &mut dyn Storage,
+    denom: &str,
+    action: impl FnOnce(&mut Ledger) -> StdResult<()>,
+) -> StdResult<Ledger> {
+    LEDGERS.update(storage, denom, |ledger| {
+        let mut ledger = ledger.unwrap_or_default();
+        action(&mut ledger)?;
+        Ok(ledger)
+    })
+}
+
+/// Applies a change to the funds owed in a denom that comes with paying out
+/// `outgoing` in that denom, returning the updated ledger.
+/// Fails if the payment would break solvency, see `ensure_solvent`.
+pub fn update_ledger_for_payout(
+    deps: DepsMut,
+    env: &Env,
+    denom: &str,
+    outgoing: Uint128,
+    action: impl FnOnce(&mut Ledger) -> StdResult<()>,
+) -> Result<Ledger, ContractError> {
+    let before = LEDGERS
+        .may_load(deps.storage, denom)?
+        .unwrap_or_default()
+        .liabilities();
+    let ledger = update_ledger(deps.storage, denom, action)?;
+    ensure_solvent(&deps.as_ref(), env, denom, before, outgoing)?;
+    Ok(ledger)
+}
+
+/// Ensures that paying out `outgoing` does not leave the contract's balance in
+/// a denom any further from covering what it owes in that denom than it was
+/// before the payment. A shortfall that already existed does not block payments
+/// that settle what they pay out, but nothing may be paid out that is not.
+/// Must be called after the ledger has been updated for the payment, with the
+/// liabilities from before the update.
+fn ensure_solvent(
+    deps: &Deps,
+    env: &Env,
+    denom: &str,
+    liabilities_before: Uint128,
+    outgoing: Uint128,
+) -> Result<(), ContractError> {
+    let liabilities = LEDGERS
+        .may_load(deps.storage, denom)?
+        .unwrap_or_default()
+        .liabilities();
+    let balance = balance(deps, env, denom)?;
+    let shortfall_before = liabilities_before.saturating_sub(balance);
+    let shortfall = liabilities.saturating_sub(balance.saturating_sub(outgoing));
+    if shortfall > shortfall_before {
+        return Err(ContractError::Insolvent {
+            denom: denom.to_string(),
+            shortfall,
+        });
+    }
+    Ok(())
+}
+
+/// Appends an entry to the audit log, returning its id.
+pub fn record_audit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    actor: Option<Addr>,
+    action: &str,
+    changes: Vec<AuditChange>,
+) -> StdResult<u64> {
+    let id = AUDIT_LOG
+        .keys(storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .map_or(0, |last| last + 1);
+    AUDIT_LOG.save(
+        storage,
+        id,
+        &AuditEntry {
+            id,
+            actor,
+            height: env.block.height,
+            action: action.to_string(),
+            changes,
+        },
+    )?;
+    Ok(id)
+}
+
+/// The prefix of the denoms that cw20 tokens are tracked under.
+const CW20_DENOM_PREFIX: &str = "cw20:";
+
+/// The denom that a cw20 token is tracked under.
+pub fn cw20_denom(token: &Addr) -> String {
+    format!("{}{}", CW20_DENOM_PREFIX, token)
+}
+
+/// The address of the cw20 token that a denom tracks, if it tracks one.
+pub fn cw20_token(denom: &str) -> Option<&str> {
+    denom.strip_prefix(CW20_DENOM_PREFIX)
+}
+
+/// The contract's balance in a native denom or cw20 token.
+pub fn balance(deps: &Deps, env: &Env, denom: &str) -> StdResult<Uint128> {
+    match cw20_token(denom) {
+        Some(token) => {
+            let res: BalanceResponse = deps.querier.query_wasm_smart(
+                token,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            Ok(res.balance)
+        }
+        None => Ok(deps
+            .querier
+            .query_balance(&env.contract.address, denom)?
+            .amount),
+    }
+}
+
+/// Messages that send coins to an address. Native coins are sent in a single
+/// bank message, while each cw20 token is sent with its own transfer.
+pub fn send_coins(to: &Addr, coins: Vec<Coin>) -> StdResult<Vec<CosmosMsg>> {
+    let (tokens, native): (Vec<_>, Vec<_>) = coins
+        .into_iter()
+        .partition(|c| cw20_token(&c.denom).is_some());
+    let mut messages = vec![];
+    if !native.is_empty() {
+        messages.push(CosmosMsg::Bank(BankMsg::Send {
+            to_address: to.to_string(),
+            amount: native,
+        }));
+    }
+    for coin in tokens {
+        messages.push(cw20_message(
+            &coin.denom,
+            &Cw20ExecuteMsg::Transfer {
+                recipient: to.to_string(),
+                amount: coin.amount,
+            },
+        )?);
+    }
+    Ok(messages)
+}
+
+/// Messages that burn coins, native or cw20.
+pub fn burn_coins(coins: Vec<Coin>) -> StdResult<Vec<CosmosMsg>> {
+    let (tokens, native): (Vec<_>, Vec<_>) = coins
+        .into_iter()
+        .partition(|c| cw20_token(&c.denom).is_some());
+    let mut messages = vec![];
+    if !native.is_empty() {
+        messages.push(CosmosMsg::Bank(BankMsg::Burn { amount: native }));
+    }
+    for coin in tokens {
+        messages.push(cw20_message(
+            &coin.denom,
+            &Cw20ExecuteMsg::Burn {
+                amount: coin.amount,
+            },
+        )?);
+    }
+    Ok(messages)
+}
+
+fn cw20_message(denom: &str, msg: &Cw20ExecuteMsg) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: cw20_token(denom).unwrap_or_default().to_string(),
+        msg: to_binary(msg)?,
+        funds: vec![],
+    }))
+}
+
+/// Checks that the native denom of `new` can replace that of `old`. It must not
+/// already be accepted as a fee denom, and nothing may still be held in the
+/// old native denom for keys or requests, as those could no longer be refunded.
+pub fn check_native_denom_change(
+    storage: &dyn Storage,
+    old: &Config,
+    new: &Config,
+) -> Result<(), ContractError> {
+    if new.native_denom == old.native_denom {
+        return Ok(());
+    }
+    if FEE_DENOMS.has(storage, &new.native_denom) {
+        return Err(ContractError::NativeDenomIsFeeDenom {
+            denom: new.native_denom.clone(),
+        });
+    }
+    let ledger = LEDGERS
+        .may_load(storage, &old.native_denom)?
+        .unwrap_or_default();
+    if !ledger.deposits.is_zero() || !ledger.bounties.is_zero() {
+        return Err(ContractError::NativeDenomInUse {
+            denom: old.native_denom.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// What whitelisting a key costs in a denom, if deposits are accepted in it.
+pub fn deposit_terms(
+    storage: &dyn Storage,
+    cfg: &Config,
+    denom: &str,
+) -> StdResult<Option<DenomDeposit>> {
+    if denom == cfg.native_denom {
+        return Ok(Some(DenomDeposit {
+            whitelist_deposit_amt: cfg.whitelist_deposit_amt,
+            refund_increment_amt: cfg.refund_increment_amt,
+        }));
+    }
+    Ok(FEE_DENOMS
+        .may_load(storage, denom)?
+        .and_then(|pricing| pricing.deposit))
+}
+
+/// Sums the coins of a denom that were sent. Coins of any other denom are
+/// either rejected or returned to be refunded, depending on the config.
+pub fn received_funds(
+    cfg: &Config,
+    funds: &[Coin],
+    denom: &str,
+) -> Result<(Uint128, Vec<Coin>), ContractError> {
+    let (accepted, foreign) = split_funds(cfg, funds, |d| d == denom)?;
+    Ok((accepted.into_iter().map(|c| c.amount).sum(), foreign))
+}
+
+/// Collects the coins that were sent in accepted denoms. Coins of any other
+/// denom are either rejected or returned to be refunded, depending on the config.
+/// Cw20 tokens are only ever received through the receive hook, so bank coins
+/// that claim to be one are never accepted.
+pub fn split_funds(
+    cfg: &Config,
+    funds: &[Coin],
+    accepted: impl Fn(&str) -> bool,
+) -> Result<(Vec<Coin>, Vec<Coin>), ContractError> {
+    let mut received = vec![];
+    let mut foreign = vec![];
+    for coin in funds {
+        if accepted(&coin.denom) && cw20_token(&coin.denom).is_none() {
+            add_coin(&mut received, &coin.denom, coin.amount);
+        } else if coin.amount.is_zero() {
+            continue;
+        } else if cfg.foreign_denoms == ForeignDenomPolicy::Refund {
+            foreign.push(coin.clone());
+        } else {
+            return Err(ContractError::UnsupportedDenom {
+                denom: coin.denom.clone(),
+            });
+        }
+    }
+    Ok((received, foreign))
+}
+
+/// Checks that no funds were sent with a message that is not paid for. Under
+/// the refund policy for foreign denoms, they are returned to be refunded
+/// instead of failing the message.
+pub fn nonpayable(cfg: &Config, funds: &[Coin]) -> Result<Vec<Coin>, ContractError> {
+    let funds: Vec<_> = funds
+        .iter()
+        .filter(|c| !c.amount.is_zero())
+        .cloned()
+        .collect();
+    if !funds.is_empty() && cfg.foreign_denoms == ForeignDenomPolicy::Reject {
+        return Err(ContractError::NonPayable {});
+    }
+    Ok(funds)
+}
+
+/// Adds an amount to a list of coins, keeping the list sorted by denom and
+/// free of zero amounts.
+pub fn add_coin(coins: &mut Vec<Coin>, denom: &str, amount: Uint128) {
+    if amount.is_zero() {
+        return;
+    }
+    match coins.binary_search_by(|c| c.denom.as_str().cmp(denom)) {
+        Ok(idx) => coins[idx].amount += amount,
+        Err(idx) => coins.insert(
+            idx,
+            Coin {
+                denom: denom.to_string(),
+                amount,
+            },
+        ),
+    }
+}